@@ -1,73 +1,163 @@
-use serde::Serialize;
-use serde_json::Value;
+use std::collections::BTreeSet;
 use std::error::Error;
-use std::fmt::Write as FmtWrite;
 use std::fs::read_dir;
-use std::path::{Path, PathBuf};
-use std::{fs, io};
-use tinytemplate::TinyTemplate;
-
-fn days(input_dir: &str) -> io::Result<Vec<u32>> {
-    let mut days = read_dir(input_dir)?
-        .flatten()
-        .filter(|e| e.path().is_file())
-        .flat_map(|e| e.file_name().into_string())
-        .flat_map(|s| s[0..2].parse::<u32>())
-        .collect::<Vec<_>>();
-    days.sort_unstable();
-    Ok(days)
-}
+use std::path::Path;
+use std::{env, fs, io};
 
-#[derive(Serialize)]
-struct Context<'a> {
-    days: &'a [u32],
+enum SolutionFile {
+    Day(u32),
+    Other(Option<u32>),
+    Skipped,
 }
 
-fn leading_zero(v: &Value, output: &mut String) -> tinytemplate::error::Result<()> {
-    if let Value::Number(n) = v {
-        if let Some(n) = n.as_u64() {
-            write!(output, "{0:02}", n)?;
-        }
+// solution files must be named dayNN.rs where NN is the 0 prefixed day between 01 and 25
+fn classify(name: &str) -> SolutionFile {
+    if name.starts_with('.') || name == "mod.rs" {
+        return SolutionFile::Skipped;
     }
 
-    Ok(())
+    let day = name.strip_prefix("day").and_then(|s| {
+        s.bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .map(char::from)
+            .collect::<String>()
+            .parse::<u32>()
+            .ok()
+    });
+
+    match day {
+        Some(d @ 1..=25) if name == format!("day{:02}.rs", d) => SolutionFile::Day(d),
+        _ => SolutionFile::Other(day),
+    }
 }
 
-fn gen_solutions_mod<P: AsRef<Path>>(p: P, days: &[u32]) -> io::Result<()> {
-    let mut tpl = TinyTemplate::new();
-    tpl.add_template("mod", include_str!("mod.rs.template"))
-        .unwrap();
-    tpl.add_formatter("leading_zero", leading_zero);
-    let s = tpl.render("mod", &Context { days }).unwrap();
+fn days(solutions_dir: &Path) -> io::Result<Vec<u32>> {
+    let mut days = BTreeSet::new();
+    let mut others = vec![];
 
-    fs::write(p, s)
-}
+    for entry in read_dir(solutions_dir)?.flatten() {
+        if !entry.path().is_file() {
+            continue;
+        }
 
-fn gen_solutions(dir: &str, days: &[u32]) -> io::Result<()> {
-    for day in days {
-        let file = PathBuf::from(format!("{}/day{:02}.rs", dir, day));
-        if file.exists() {
+        let Ok(name) = entry.file_name().into_string() else {
+            warning(format!(
+                "ignoring {:?}: not a valid UTF-8 file name",
+                entry.path()
+            ));
             continue;
+        };
+
+        match classify(&name) {
+            SolutionFile::Day(day) => _ = days.insert(day),
+            SolutionFile::Other(day) => others.push((name, day)),
+            SolutionFile::Skipped => {}
         }
+    }
 
-        fs::copy("solution.rs.template", file)?;
+    others.sort();
+    for (name, day) in others {
+        match day {
+            Some(day) if days.contains(&day) => warning(format!(
+                "ignoring {name}: duplicate solution for day {day}, using day{day:02}.rs"
+            )),
+            Some(day) if (1..=25).contains(&day) => warning(format!(
+                "ignoring {name}: looks like day {day} but is not named day{day:02}.rs"
+            )),
+            _ => warning(format!(
+                "ignoring {name}: solution files must be named dayNN.rs with NN between 01 and 25"
+            )),
+        }
     }
 
-    Ok(())
+    Ok(days.into_iter().collect())
+}
+
+fn warning(msg: String) {
+    println!("cargo:warning={msg}");
+}
+
+// AOC_DAYS restricts the compiled days, for example "6" or "1-5,10"
+fn selected_days(spec: &str) -> Result<BTreeSet<u32>, Box<dyn Error>> {
+    let mut days = BTreeSet::new();
+    for part in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (start.trim().parse::<u32>()?, end.trim().parse()?);
+                if start > end {
+                    return Err(format!("reversed range {part}").into());
+                }
+                days.extend(start..=end);
+            }
+            None => _ = days.insert(part.parse()?),
+        }
+    }
+    if days.is_empty() {
+        return Err("no day selected".into());
+    }
+
+    Ok(days)
+}
+
+// the days listed by the registry, which is generated by `aoc new`
+fn registered_days(registry: &Path) -> io::Result<BTreeSet<u32>> {
+    Ok(fs::read_to_string(registry)?
+        .lines()
+        .flat_map(|l| {
+            l.trim()
+                .strip_prefix("mod day")?
+                .strip_suffix(';')?
+                .parse()
+                .ok()
+        })
+        .collect())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let input_dir = "./input";
-    let output_dir = "./src/solutions";
-    let solutions_mod_output_path = Path::new(&output_dir).join("mod.rs");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR")?;
+    let solutions_dir = Path::new(&manifest_dir).join("src").join("solutions");
 
-    let days = days(input_dir)?;
+    println!("cargo:rerun-if-changed=src/solutions");
+    println!("cargo:rerun-if-env-changed=AOC_DAYS");
+    println!("cargo:rustc-check-cfg=cfg(aoc_days_subset)");
+    let values = (1..=25).map(|d| format!("\"{d}\"")).collect::<Vec<_>>();
+    println!(
+        "cargo:rustc-check-cfg=cfg(aoc_day, values({}))",
+        values.join(", ")
+    );
 
-    // write solutions mod file
-    gen_solutions_mod(solutions_mod_output_path, &days)?;
+    let registered = registered_days(&solutions_dir.join("mod.rs"))?;
+    for day in days(&solutions_dir)?
+        .into_iter()
+        .filter(|d| !registered.contains(d))
+    {
+        warning(format!(
+            "day{day:02}.rs is not registered, run `cargo run -- new {day}` to add it"
+        ));
+    }
 
-    // write solutions
-    gen_solutions(output_dir, &days)?;
+    // an empty AOC_DAYS is the same as an unset one
+    let selected = match env::var("AOC_DAYS") {
+        Ok(spec) if !spec.trim().is_empty() => {
+            Some(selected_days(&spec).map_err(|e| format!("invalid AOC_DAYS: {e}"))?)
+        }
+        _ => None,
+    };
+    if let Some(selected) = &selected {
+        // shared code only used by excluded days is expected to be dead
+        println!("cargo:rustc-cfg=aoc_days_subset");
+        for day in selected.iter().filter(|d| !registered.contains(d)) {
+            warning(format!("AOC_DAYS selects day {day} which has no solution"));
+        }
+        if selected.iter().all(|d| !registered.contains(d)) {
+            return Err("invalid AOC_DAYS: none of the selected days has a solution".into());
+        }
+    }
+
+    // the registry only compiles the days with a matching aoc_day cfg
+    for day in selected.unwrap_or_else(|| (1..=25).collect()) {
+        println!("cargo:rustc-cfg=aoc_day=\"{day}\"");
+    }
 
     Ok(())
 }
@@ -0,0 +1,189 @@
+use crate::grid::{Coord, Grid};
+use crate::image::{GifEncoder, Image, Rgb};
+use anyhow::{bail, Context};
+use fnv::FnvHashSet;
+use std::fs;
+use std::io::{stdout, IsTerminal, Write};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+// where a recording goes: a replay in the terminal without a target, an animated GIF for a
+// .gif target, and a directory of PPM frames otherwise
+pub struct AnimationOutput {
+    pub target: Option<PathBuf>,
+    pub fps: u32,
+    pub scale: usize,
+}
+
+impl Default for AnimationOutput {
+    fn default() -> Self {
+        Self {
+            target: None,
+            fps: 10,
+            scale: 4,
+        }
+    }
+}
+
+// the frames of a simulation, stored as the first frame and the cells changed by each following frame
+pub struct Recorder<T> {
+    first: Grid<T>,
+    current: Grid<T>,
+    diffs: Vec<Vec<((usize, usize), T)>>,
+}
+
+#[allow(unused)]
+impl<T: Clone + PartialEq> Recorder<T> {
+    pub fn new(first: Grid<T>) -> Self {
+        Self {
+            current: first.clone(),
+            first,
+            diffs: vec![],
+        }
+    }
+
+    // the last recorded frame
+    pub fn current(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.diffs.len() + 1
+    }
+
+    // records a new frame from the whole grid, which must have the size of the first frame
+    pub fn snapshot(&mut self, grid: &Grid<T>) {
+        assert!(
+            (grid.w, grid.h) == (self.first.w, self.first.h),
+            "snapshot of a {}x{} grid in a {}x{} recording",
+            grid.w,
+            grid.h,
+            self.first.w,
+            self.first.h
+        );
+        let diff = grid
+            .iter_with_coords()
+            .filter(|&(c, v)| self.current.get(c) != Some(v))
+            .map(|(c, v)| (c, v.clone()))
+            .collect();
+        self.push_diff(diff);
+    }
+
+    // records a new frame from the previous one with some cells changed,
+    // cells outside of the grid are ignored
+    pub fn push(&mut self, changes: impl IntoIterator<Item = (impl Coord, T)>) {
+        let diff = changes
+            .into_iter()
+            .map(|(c, v)| (c.coords(), v))
+            .filter(|(c, _)| self.current.contains_coord(c))
+            .collect();
+        self.push_diff(diff);
+    }
+
+    fn push_diff(&mut self, diff: Vec<((usize, usize), T)>) {
+        for (c, v) in &diff {
+            if let Some(cell) = self.current.get_mut(c) {
+                *cell = v.clone();
+            }
+        }
+        self.diffs.push(diff);
+    }
+
+    // calls f with each frame in order
+    pub fn for_each_frame(
+        &self,
+        mut f: impl FnMut(usize, &Grid<T>) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut frame = self.first.clone();
+        f(0, &frame)?;
+        for (i, diff) in self.diffs.iter().enumerate() {
+            for (c, v) in diff {
+                if let Some(cell) = frame.get_mut(c) {
+                    *cell = v.clone();
+                }
+            }
+            f(i + 1, &frame)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_gif(
+        &self,
+        fps: u32,
+        scale: usize,
+        color: impl Fn(&T) -> Rgb,
+    ) -> anyhow::Result<Vec<u8>> {
+        let colors = self
+            .first
+            .iter_with_coords()
+            .map(|(_, v)| color(v))
+            .chain(self.diffs.iter().flatten().map(|(_, v)| color(v)))
+            .collect::<FnvHashSet<_>>();
+        let scale = scale.max(1);
+        let mut gif = GifEncoder::new(self.first.w * scale, self.first.h * scale, colors)?;
+        let delay = (100 / fps.max(1)).max(1) as u16;
+        self.for_each_frame(|_, frame| {
+            gif.add_frame(&Image::from_grid(frame, scale, |_, v| color(v)), delay)
+        })?;
+        Ok(gif.finish())
+    }
+
+    // writes frame_00000.ppm, frame_00001.ppm... in the directory
+    pub fn save_ppm_frames(
+        &self,
+        dir: impl Into<PathBuf>,
+        scale: usize,
+        color: impl Fn(&T) -> Rgb,
+    ) -> anyhow::Result<()> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("unable to create {}", dir.display()))?;
+        self.for_each_frame(|i, frame| {
+            Image::from_grid(frame, scale, |_, v| color(v))
+                .save(dir.join(format!("frame_{i:05}.ppm")))
+        })
+    }
+
+    // plays the frames in place in a terminal, or prints them one after the other otherwise
+    pub fn replay(&self, fps: u32, render: impl Fn(&Grid<T>) -> String) -> anyhow::Result<()> {
+        let terminal = stdout().is_terminal();
+        let delay = Duration::from_secs(1) / fps.max(1);
+        let mut out = stdout().lock();
+        self.for_each_frame(|i, frame| {
+            if terminal {
+                // move to the top left corner and clear the screen
+                write!(out, "\x1b[H\x1b[2J")?;
+            } else if i > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "frame {}/{}", i + 1, self.frame_count())?;
+            write!(out, "{}", render(frame))?;
+            out.flush()?;
+            if terminal {
+                sleep(delay);
+            }
+            Ok(())
+        })
+    }
+
+    pub fn export(
+        &self,
+        output: &AnimationOutput,
+        render: impl Fn(&Grid<T>) -> String,
+        color: impl Fn(&T) -> Rgb,
+    ) -> anyhow::Result<()> {
+        let Some(target) = &output.target else {
+            return self.replay(output.fps, render);
+        };
+
+        match target.extension().and_then(|e| e.to_str()) {
+            Some("gif") => {
+                let gif = self.to_gif(output.fps, output.scale, color)?;
+                fs::write(target, gif)
+                    .with_context(|| format!("unable to write {}", target.display()))
+            }
+            Some(_) => bail!("unsupported animation format, expected .gif or a directory"),
+            None => self.save_ppm_frames(target, output.scale, color),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::config::{Config, STATE_DIR};
+use crate::solver::{input_exists, input_file};
+use crate::vault;
+use anyhow::{anyhow, bail, Context};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const YEAR: u32 = 2024;
+const USER_AGENT: &str = "github.com/noirotm/advent-of-code-2024 by marc.noirot@gmail.com";
+
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    min_delay: Duration,
+    last_request_file: PathBuf,
+}
+
+impl Client {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        let session = config.session.clone().ok_or_else(|| {
+            anyhow!("no session token, set AOC_SESSION or `session` in {STATE_DIR}/config")
+        })?;
+
+        Ok(Self {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+            base_url: config.base_url.clone(),
+            session,
+            min_delay: config.min_delay,
+            last_request_file: config.state_dir.join("last-request"),
+        })
+    }
+
+    pub fn fetch_input(&self, day: u32) -> anyhow::Result<String> {
+        let url = format!("{}/{YEAR}/day/{day}/input", self.base_url);
+        self.throttle()?;
+
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .call()
+            .with_context(|| format!("unable to fetch {url}"))?;
+
+        Ok(response.into_string()?)
+    }
+
+    pub fn post_answer(&self, day: u32, part: u8, answer: &str) -> anyhow::Result<String> {
+        let url = format!("{}/{YEAR}/day/{day}/answer", self.base_url);
+        self.throttle()?;
+
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])
+            .with_context(|| format!("unable to post to {url}"))?;
+
+        Ok(response.into_string()?)
+    }
+
+    // the last request time is shared by all the invocations of the program,
+    // so that running several commands in a row still respects the delay
+    fn throttle(&self) -> anyhow::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+        let last_request = fs::read_to_string(&self.last_request_file)
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(Duration::from_millis);
+        if let Some(elapsed) = last_request.and_then(|t| now.checked_sub(t)) {
+            if elapsed < self.min_delay {
+                sleep(self.min_delay - elapsed);
+            }
+        }
+
+        if let Some(dir) = self.last_request_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        fs::write(&self.last_request_file, now.as_millis().to_string())?;
+
+        Ok(())
+    }
+}
+
+pub fn fetch(day: u32) -> anyhow::Result<()> {
+    if !(1..=25).contains(&day) {
+        bail!("invalid day {day}, expected a value between 1 and 25");
+    }
+
+    fetch_with(&Config::load()?, day, Path::new(&input_file(day)))
+}
+
+fn fetch_with(config: &Config, day: u32, path: &Path) -> anyhow::Result<()> {
+    if input_exists(path) {
+        println!("{} is already cached", path.display());
+        return Ok(());
+    }
+
+    let client = Client::new(config)?;
+    let input = client.fetch_input(day)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // with a vault key the plain text is never written to disk
+    if vault::is_configured() {
+        println!("saved {}", vault::store(path, input.as_bytes())?.display());
+    } else {
+        fs::write(path, input).with_context(|| format!("unable to write {}", path.display()))?;
+        println!("saved {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submit::tests::serve;
+    use std::time::Duration;
+
+    #[test]
+    fn fetch_from_server() {
+        let (base_url, server) = serve(vec!["1 2\n3 4\n"]);
+        let dir = std::env::temp_dir().join(format!("aoc-fetch-{}", std::process::id()));
+        let config = Config {
+            session: Some("token".to_string()),
+            base_url,
+            min_delay: Duration::ZERO,
+            state_dir: dir.join("state"),
+        };
+        let path = dir.join("input").join("07.txt");
+
+        fetch_with(&config, 7, &path).unwrap();
+        // the vault is used instead when the key is set in the environment
+        let saved = if vault::is_configured() {
+            vault::decrypt_file(vault::encrypted_path(&path))
+        } else {
+            fs::read(&path).map_err(anyhow::Error::from)
+        };
+        assert_eq!(saved.unwrap(), b"1 2\n3 4\n");
+        // already cached, the server only accepts one connection
+        fetch_with(&config, 7, &path).unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2024/day/7/input HTTP/1.1"));
+        assert!(requests[0].contains("\r\nCookie: session=token\r\n"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
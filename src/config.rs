@@ -0,0 +1,67 @@
+use anyhow::Context;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, fs, io};
+
+pub const STATE_DIR: &str = ".aoc";
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_MIN_DELAY: Duration = Duration::from_secs(5);
+
+pub fn state_file(name: &str) -> PathBuf {
+    PathBuf::from(STATE_DIR).join(name)
+}
+
+// settings are read from .aoc/config as `key = value` lines,
+// each of them can be overridden by the matching AOC_* environment variable
+pub struct Config {
+    pub session: Option<String>,
+    pub base_url: String,
+    pub min_delay: Duration,
+    // where the request times and the answer history are kept
+    pub state_dir: PathBuf,
+}
+
+impl Config {
+    pub fn load() -> anyhow::Result<Self> {
+        let file = match fs::read_to_string(state_file("config")) {
+            Ok(s) => parse_config(&s),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e).context("unable to read the configuration file"),
+        };
+        let get = |key: &str| {
+            env::var(format!("AOC_{}", key.to_ascii_uppercase()))
+                .ok()
+                .or_else(|| file.get(key).cloned())
+                .filter(|v| !v.is_empty())
+        };
+
+        let min_delay = match get("min_delay") {
+            Some(s) => s
+                .parse()
+                .map_err(anyhow::Error::from)
+                .and_then(|s| Ok(Duration::try_from_secs_f64(s)?))
+                .context("invalid min_delay")?,
+            None => DEFAULT_MIN_DELAY,
+        };
+
+        Ok(Self {
+            session: get("session"),
+            base_url: get("base_url")
+                .map(|s| s.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string()),
+            min_delay,
+            state_dir: PathBuf::from(STATE_DIR),
+        })
+    }
+}
+
+fn parse_config(s: &str) -> HashMap<String, String> {
+    s.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .flat_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
@@ -0,0 +1,58 @@
+use std::ops::Range;
+
+pub struct GenParams {
+    pub size: usize,
+    pub density: f64,
+}
+
+impl Default for GenParams {
+    fn default() -> Self {
+        Self {
+            size: 100,
+            density: 0.1,
+        }
+    }
+}
+
+// SplitMix64, small and stable, so that a seed always produces the same input
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn range(&mut self, r: Range<u64>) -> u64 {
+        r.start + self.next_u64() % (r.end - r.start).max(1)
+    }
+
+    #[cfg_attr(aoc_days_subset, allow(dead_code))]
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    #[cfg_attr(aoc_days_subset, allow(dead_code))]
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    #[cfg_attr(aoc_days_subset, allow(dead_code))]
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    #[cfg_attr(aoc_days_subset, allow(dead_code))]
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use anyhow::anyhow;
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::fmt::{Display, Error, Formatter};
-use std::io::{BufRead, BufReader, Read};
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 use std::str::FromStr;
 
 #[derive(Clone, Debug)]
@@ -29,54 +31,96 @@ impl<T> Grid<T> {
         Self { cells, w, h }
     }
 
-    pub fn from_reader_callback<R, F, E>(r: R, f: F) -> Result<Self, E>
-    where
-        R: Read,
-        F: FnMut(u8) -> Result<T, E> + Copy,
-    {
-        let cells = BufReader::new(r)
-            .lines()
-            .map_while(Result::ok)
-            .map(|l| l.bytes().map(f).collect::<Result<Vec<_>, _>>())
-            .collect::<Result<Vec<_>, _>>()?;
-        let h = cells.len();
-        let w = cells.first().map_or(0, |c| c.len());
+    // the cells row by row, None if there are not exactly w * h of them
+    pub fn from_cells(cells: Vec<T>, w: usize, h: usize) -> Option<Self> {
+        (cells.len() == w * h).then_some(Self { cells, w, h })
+    }
+
+    // the lines of the input without the line endings, the trailing empty lines are ignored
+    fn read_lines<R: Read, E>(r: R) -> Result<Vec<Vec<u8>>, GridError<E>> {
+        let mut lines = BufReader::new(r)
+            .split(b'\n')
+            .map(|l| {
+                let mut l = l?;
+                if l.last() == Some(&b'\r') {
+                    l.pop();
+                }
+                Ok(l)
+            })
+            .collect::<Result<Vec<_>, io::Error>>()
+            .map_err(GridError::Io)?;
+        while lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        Ok(lines)
+    }
+
+    // every row must have the width of the first one, and there must be at least one row
+    fn from_rows<E>(
+        lines: Vec<Vec<u8>>,
+        mut parse_row: impl FnMut(&[u8]) -> Result<Vec<T>, (usize, E)>,
+    ) -> Result<Self, GridError<E>> {
+        let mut cells = vec![];
+        let mut w = 0;
+        for (row, line) in lines.iter().enumerate() {
+            let values = parse_row(line).map_err(|(col, error)| GridError::Cell {
+                row: row + 1,
+                col: col + 1,
+                error,
+            })?;
+            if row == 0 {
+                w = values.len();
+            } else if values.len() != w {
+                return Err(GridError::Ragged {
+                    row: row + 1,
+                    width: values.len(),
+                    expected: w,
+                });
+            }
+            cells.extend(values);
+        }
+        if cells.is_empty() {
+            return Err(GridError::Empty);
+        }
 
         Ok(Self {
-            cells: cells.into_iter().flatten().collect(),
+            cells,
             w,
-            h,
+            h: lines.len(),
+        })
+    }
+
+    pub fn from_reader_callback<R, F, E>(r: R, mut f: F) -> Result<Self, GridError<E>>
+    where
+        R: Read,
+        F: FnMut(u8) -> Result<T, E>,
+    {
+        Self::from_rows(Self::read_lines(r)?, |line| {
+            line.iter()
+                .enumerate()
+                .map(|(col, &b)| f(b).map_err(|e| (col, e)))
+                .collect()
         })
     }
 
-    pub fn from_reader<R: Read>(r: R) -> Result<Self, T::Error>
+    pub fn from_reader<R: Read>(r: R) -> Result<Self, GridError<T::Error>>
     where
         T: TryFrom<u8>,
     {
         Self::from_reader_callback(r, T::try_from)
     }
 
-    pub fn from_split_whitespace_reader<R>(r: R) -> Result<Self, T::Err>
+    pub fn from_split_whitespace_reader<R>(r: R) -> Result<Self, GridError<T::Err>>
     where
         T: FromStr,
         R: Read,
     {
-        let cells = BufReader::new(r)
-            .lines()
-            .map_while(Result::ok)
-            .map(|l| {
-                l.split_whitespace()
-                    .map(T::from_str)
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let h = cells.len();
-        let w = cells.first().map_or(0, |c| c.len());
-
-        Ok(Self {
-            cells: cells.into_iter().flatten().collect(),
-            w,
-            h,
+        Self::from_rows(Self::read_lines(r)?, |line| {
+            String::from_utf8_lossy(line)
+                .split_whitespace()
+                .enumerate()
+                .map(|(col, v)| T::from_str(v).map_err(|e| (col, e)))
+                .collect()
         })
     }
 
@@ -84,40 +128,31 @@ impl<T> Grid<T> {
     where
         T: Clone + Default,
     {
-        let mut min_x = 0;
-        let mut max_x = 0;
-        let mut min_y = 0;
-        let mut max_y = 0;
-        for pt in points.keys() {
-            if pt.x < min_x {
-                min_x = pt.x;
-            }
-            if pt.x > max_x {
-                max_x = pt.x;
-            }
-            if pt.y < min_y {
-                min_y = pt.y;
-            }
-            if pt.y > max_y {
-                max_y = pt.y;
-            }
-        }
+        Self::from_map_with_origin(points).0
+    }
+
+    // also returns the original coordinates of the (0, 0) cell of the grid
+    pub fn from_map_with_origin(points: HashMap<Point, T>) -> (Self, Point)
+    where
+        T: Clone + Default,
+    {
+        let min_x = points.keys().map(|pt| pt.x).min().unwrap_or_default();
+        let max_x = points.keys().map(|pt| pt.x).max().unwrap_or(-1);
+        let min_y = points.keys().map(|pt| pt.y).min().unwrap_or_default();
+        let max_y = points.keys().map(|pt| pt.y).max().unwrap_or(-1);
         let w = (max_x - min_x + 1) as usize;
         let h = (max_y - min_y + 1) as usize;
-        let x_offset = -min_x;
-        let y_offset = -min_y;
 
         let mut grid = Self::new(w, h);
 
+        let origin = Point::new(min_x, min_y);
         for (pt, cell) in points {
-            let x = (pt.x + x_offset) as usize;
-            let y = (pt.y + y_offset) as usize;
-            if let Some(v) = grid.get_mut((x, y)) {
+            if let Some(v) = pt.to_coords(origin).and_then(|c| grid.get_mut(c)) {
                 *v = cell;
             }
         }
 
-        grid
+        (grid, origin)
     }
 
     pub fn get(&self, c: impl Coord) -> Option<&T> {
@@ -141,55 +176,118 @@ impl<T> Grid<T> {
     }
 
     pub fn neighbours4(&self, c: impl Coord) -> Vec<&T> {
-        self.neighbours_coords4(c)
-            .iter()
-            .flat_map(|&c| self.get(c))
-            .collect()
+        self.iter_neighbours4(c).collect()
     }
 
     pub fn neighbours_coords4(&self, c: impl Coord) -> Vec<(usize, usize)> {
-        [(-1, 0), (0, -1), (0, 1), (1, 0)]
-            .iter()
-            .flat_map(|&(dx, dy)| self.neighbour_coords(&c, dx, dy))
-            .collect()
+        self.iter_neighbours_coords4(c).collect()
     }
 
     pub fn neighbours8(&self, c: impl Coord) -> Vec<&T> {
-        self.neighbours_coords8(c)
-            .iter()
-            .flat_map(|&c| self.get(c))
-            .collect()
+        self.iter_neighbours8(c).collect()
     }
 
     pub fn neighbours_coords8(&self, c: impl Coord) -> Vec<(usize, usize)> {
-        [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ]
-        .iter()
-        .flat_map(|&(dx, dy)| self.neighbour_coords(&c, dx, dy))
-        .collect()
+        self.iter_neighbours_coords8(c).collect()
+    }
+
+    // same as the functions above, without allocating
+    pub fn iter_neighbours_coords4(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let c = c.coords();
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.neighbour_coords(&c, d.dx(), d.dy()))
+    }
+
+    pub fn iter_neighbours4(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.iter_neighbours4_with_coords(c).map(|(_, v)| v)
+    }
+
+    pub fn iter_neighbours4_with_coords(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.iter_neighbours_coords4(c)
+            .map(|n| (n, &self.cells[n.x() + n.y() * self.w]))
+    }
+
+    pub fn iter_neighbours_coords8(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let c = c.coords();
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |d| self.neighbour_coords(&c, d.dx(), d.dy()))
+    }
+
+    pub fn iter_neighbours8(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.iter_neighbours8_with_coords(c).map(|(_, v)| v)
+    }
+
+    pub fn iter_neighbours8_with_coords(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.iter_neighbours_coords8(c)
+            .map(|n| (n, &self.cells[n.x() + n.y() * self.w]))
     }
 
     fn neighbour_coords(&self, c: &impl Coord, dx: isize, dy: isize) -> Option<(usize, usize)> {
-        if (c.x() == 0 && dx == -1)
-            || (c.y() == 0 && dy == -1)
-            || (c.x() == self.w - 1 && dx == 1)
-            || (c.y() == self.h - 1 && dy == 1)
-        {
-            None
-        } else {
-            Some((
-                ((c.x() as isize) + dx) as usize,
-                ((c.y() as isize) + dy) as usize,
-            ))
+        let n = c.checked_add_offset((dx, dy))?;
+        self.contains_coord(&n).then_some(n)
+    }
+
+    // the coordinates moved by the offset, wrapping around the edges as on a torus,
+    // None only for an empty grid
+    pub fn wrapping_add_offset(
+        &self,
+        c: impl Coord,
+        offset: impl Into<(isize, isize)>,
+    ) -> Option<(usize, usize)> {
+        if self.w == 0 || self.h == 0 {
+            return None;
         }
+
+        let (dx, dy) = offset.into();
+        let wrap = |v: usize, d: isize, len: usize| {
+            ((v % len) as isize + d % len as isize).rem_euclid(len as isize) as usize
+        };
+        Some((wrap(c.x(), dx, self.w), wrap(c.y(), dy, self.h)))
+    }
+
+    // on grids smaller than 3x3, the same neighbour can appear more than once
+    pub fn wrapping_neighbours4(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.wrapping_neighbours_coords4(c)
+            .map(|n| &self.cells[n.x() + n.y() * self.w])
+    }
+
+    pub fn wrapping_neighbours_coords4(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let c = c.coords();
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.wrapping_add_offset(c, d))
+    }
+
+    pub fn wrapping_neighbours8(&self, c: impl Coord) -> impl Iterator<Item = &T> + '_ {
+        self.wrapping_neighbours_coords8(c)
+            .map(|n| &self.cells[n.x() + n.y() * self.w])
+    }
+
+    pub fn wrapping_neighbours_coords8(
+        &self,
+        c: impl Coord,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let c = c.coords();
+        Direction8::ALL
+            .into_iter()
+            .filter_map(move |d| self.wrapping_add_offset(c, d))
     }
 
     pub fn iter_row(&self, row: usize) -> RowIter<'_, T> {
@@ -208,6 +306,46 @@ impl<T> Grid<T> {
         }
     }
 
+    // the cells seen from `from` (excluded) going in `step` until the edge of the grid,
+    // step being a Direction, a Direction8, or any (dx, dy) vector, (0, 0) gives no cell
+    pub fn ray(&self, from: impl Coord, step: impl Into<(isize, isize)>) -> Ray<'_, T> {
+        let step = step.into();
+        Ray {
+            grid: self,
+            pos: from.checked_add_offset(step).filter(|_| step != (0, 0)),
+            step,
+        }
+    }
+
+    // same as ray, but wrapping around the edges, until it comes back to `from` (excluded)
+    pub fn wrapping_ray(
+        &self,
+        from: impl Coord,
+        step: impl Into<(isize, isize)>,
+    ) -> WrappingRay<'_, T> {
+        let step = step.into();
+        let from = self.wrapping_add_offset(from, (0, 0));
+        WrappingRay {
+            grid: self,
+            pos: from.and_then(|c| self.wrapping_add_offset(c, step)),
+            from,
+            step,
+        }
+    }
+
+    // same as ray, but stops before the first cell matching the predicate
+    pub fn ray_until<P>(
+        &self,
+        from: impl Coord,
+        step: impl Into<(isize, isize)>,
+        mut stop: P,
+    ) -> impl Iterator<Item = ((usize, usize), &T)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.ray(from, step).take_while(move |(_, v)| !stop(v))
+    }
+
     pub fn iter_with_coords(&self) -> IterWithCoords<'_, T> {
         IterWithCoords {
             grid: self,
@@ -223,24 +361,90 @@ impl<T> Grid<T> {
     pub fn as_slice_mut(&mut self) -> &mut [T] {
         &mut self.cells
     }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            w: self.w,
+            h: self.h,
+        }
+    }
 }
 
 impl<T> FromStr for Grid<T>
 where
     T: TryFrom<u8>,
 {
-    type Err = T::Error;
+    type Err = GridError<T::Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Grid::from_reader(s.as_bytes())
     }
 }
 
+#[derive(Debug)]
+pub enum GridError<E> {
+    Io(io::Error),
+    Empty,
+    // rows and columns are numbered from 1
+    Ragged {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    Cell {
+        row: usize,
+        col: usize,
+        error: E,
+    },
+}
+
+#[allow(unused)]
+impl<E> GridError<E> {
+    // for the inputs where an empty grid is valid:
+    // Grid::from_reader(r).or_else(GridError::allow_empty)
+    pub fn allow_empty<T>(self) -> Result<Grid<T>, Self> {
+        match self {
+            GridError::Empty => Ok(Grid {
+                cells: vec![],
+                w: 0,
+                h: 0,
+            }),
+            e => Err(e),
+        }
+    }
+}
+
+impl<E: Display> Display for GridError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            GridError::Io(e) => write!(f, "unable to read the grid: {e}"),
+            GridError::Empty => write!(f, "empty grid"),
+            GridError::Ragged {
+                row,
+                width,
+                expected,
+            } => write!(
+                f,
+                "row {row} has {width} cells, expected {expected} like the first row"
+            ),
+            GridError::Cell { row, col, error } => {
+                write!(f, "invalid cell at row {row}, column {col}: {error}")
+            }
+        }
+    }
+}
+
+impl<E: Display + Debug> std::error::Error for GridError<E> {}
+
 impl<T> Display for Grid<T>
 where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.w == 0 {
+            return Ok(());
+        }
         for row in self.cells.chunks(self.w) {
             for cell in row {
                 write!(f, "{}", cell)?;
@@ -257,11 +461,13 @@ pub trait Coord {
     fn y(&self) -> usize;
     fn coords(&self) -> (usize, usize);
 
-    fn add_offset(&self, other: (isize, isize)) -> (usize, usize) {
-        (
-            (self.x() as isize + other.0) as usize,
-            (self.y() as isize + other.1) as usize,
-        )
+    // None when going past 0 or usize::MAX
+    fn checked_add_offset(&self, other: impl Into<(isize, isize)>) -> Option<(usize, usize)> {
+        let other = other.into();
+        Some((
+            self.x().checked_add_signed(other.0)?,
+            self.y().checked_add_signed(other.1)?,
+        ))
     }
 
     fn diff(&self, other: &impl Coord) -> (isize, isize) {
@@ -278,6 +484,18 @@ pub struct GridPoint {
     pub y: usize,
 }
 
+impl From<(usize, usize)> for GridPoint {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<GridPoint> for (usize, usize) {
+    fn from(pt: GridPoint) -> Self {
+        (pt.x, pt.y)
+    }
+}
+
 impl Coord for GridPoint {
     fn x(&self) -> usize {
         self.x
@@ -334,12 +552,568 @@ impl Coord for &(usize, usize) {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
 pub struct Point {
     pub x: i64,
     pub y: i64,
 }
 
+#[allow(unused)]
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    // the point at the grid coordinates, for a grid whose (0, 0) cell is at origin
+    pub fn from_coords(c: impl Coord, origin: Point) -> Option<Self> {
+        Some(Self::new(
+            i64::try_from(c.x()).ok()?.checked_add(origin.x)?,
+            i64::try_from(c.y()).ok()?.checked_add(origin.y)?,
+        ))
+    }
+
+    // the grid coordinates of the point, None when it is above or left of origin
+    pub fn to_coords(self, origin: Point) -> Option<(usize, usize)> {
+        Some((
+            usize::try_from(self.x.checked_sub(origin.x)?).ok()?,
+            usize::try_from(self.y.checked_sub(origin.y)?).ok()?,
+        ))
+    }
+
+    pub fn manhattan(&self, other: Point) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    pub fn chebyshev(&self, other: Point) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    // rotations around (0, 0), with y growing downwards like Direction
+    pub fn rotate_cw(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        -*self
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(d: Direction) -> Self {
+        Self::new(d.dx() as i64, d.dy() as i64)
+    }
+}
+
+impl From<Direction8> for Point {
+    fn from(d: Direction8) -> Self {
+        Self::new(d.dx() as i64, d.dy() as i64)
+    }
+}
+
+impl TryFrom<(usize, usize)> for Point {
+    type Error = anyhow::Error;
+
+    fn try_from(c: (usize, usize)) -> Result<Self, Self::Error> {
+        Self::from_coords(c, Point::default()).ok_or_else(|| anyhow!("coordinates out of range"))
+    }
+}
+
+impl TryFrom<Point> for (usize, usize) {
+    type Error = anyhow::Error;
+
+    fn try_from(pt: Point) -> Result<Self, Self::Error> {
+        pt.to_coords(Point::default())
+            .ok_or_else(|| anyhow!("negative coordinates"))
+    }
+}
+
+// a Point, a Direction, a Direction8 or a (i64, i64) can be added to a Point
+impl<P: Into<Point>> Add<P> for Point {
+    type Output = Point;
+
+    fn add(self, other: P) -> Self::Output {
+        let other = other.into();
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<P: Into<Point>> AddAssign<P> for Point {
+    fn add_assign(&mut self, other: P) {
+        *self = *self + other;
+    }
+}
+
+impl<P: Into<Point>> Sub<P> for Point {
+    type Output = Point;
+
+    fn sub(self, other: P) -> Self::Output {
+        let other = other.into();
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<P: Into<Point>> SubAssign<P> for Point {
+    fn sub_assign(&mut self, other: P) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, k: i64) -> Self::Output {
+        Self::new(self.x * k, self.y * k)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+// y grows downwards, so North is (0, -1)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[allow(unused)]
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Direction> {
+        Self::ALL.into_iter()
+    }
+
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
+    pub fn from_offset(offset: (isize, isize)) -> Option<Self> {
+        Self::iter().find(|d| d.offset() == offset)
+    }
+
+    pub fn dx(&self) -> isize {
+        self.offset().0
+    }
+
+    pub fn dy(&self) -> isize {
+        self.offset().1
+    }
+
+    pub fn turn_right(&self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn turn_left(&self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_around(&self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    // the position in Direction::ALL
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl From<Direction> for (isize, isize) {
+    fn from(value: Direction) -> Self {
+        value.offset()
+    }
+}
+
+impl TryFrom<u8> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'^' | b'N' => Ok(Direction::North),
+            b'>' | b'E' => Ok(Direction::East),
+            b'v' | b'S' => Ok(Direction::South),
+            b'<' | b'W' => Ok(Direction::West),
+            _ => Err(anyhow!("Invalid direction")),
+        }
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        u8::try_from(value)
+            .map_err(|_| anyhow!("Invalid direction"))
+            .and_then(Direction::try_from)
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+#[allow(unused)]
+impl Direction8 {
+    // clockwise, starting from North
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Direction8> {
+        Self::ALL.into_iter()
+    }
+
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction8::North => (0, -1),
+            Direction8::NorthEast => (1, -1),
+            Direction8::East => (1, 0),
+            Direction8::SouthEast => (1, 1),
+            Direction8::South => (0, 1),
+            Direction8::SouthWest => (-1, 1),
+            Direction8::West => (-1, 0),
+            Direction8::NorthWest => (-1, -1),
+        }
+    }
+
+    pub fn dx(&self) -> isize {
+        self.offset().0
+    }
+
+    pub fn dy(&self) -> isize {
+        self.offset().1
+    }
+
+    fn rotate(&self, eighths: usize) -> Self {
+        let i = Self::ALL.iter().position(|d| d == self).unwrap_or_default();
+        Self::ALL[(i + eighths) % 8]
+    }
+
+    // turns by 45 degrees
+    pub fn turn_right(&self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn turn_left(&self) -> Self {
+        self.rotate(7)
+    }
+
+    pub fn turn_around(&self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.dx() != 0 && self.dy() != 0
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::North => Direction8::North,
+            Direction::East => Direction8::East,
+            Direction::South => Direction8::South,
+            Direction::West => Direction8::West,
+        }
+    }
+}
+
+impl From<Direction8> for (isize, isize) {
+    fn from(value: Direction8) -> Self {
+        value.offset()
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "N" | "^" => Ok(Direction8::North),
+            "NE" => Ok(Direction8::NorthEast),
+            "E" | ">" => Ok(Direction8::East),
+            "SE" => Ok(Direction8::SouthEast),
+            "S" | "v" => Ok(Direction8::South),
+            "SW" => Ok(Direction8::SouthWest),
+            "W" | "<" => Ok(Direction8::West),
+            "NW" => Ok(Direction8::NorthWest),
+            _ => Err(anyhow!("Invalid direction")),
+        }
+    }
+}
+
+// a bit-packed Grid<bool>, for sets of coordinates
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BitGrid {
+    bits: Vec<u64>,
+    pub w: usize,
+    pub h: usize,
+}
+
+#[allow(unused)]
+impl BitGrid {
+    pub fn new(w: usize, h: usize) -> Self {
+        Self {
+            bits: vec![0; (w * h).div_ceil(64)],
+            w,
+            h,
+        }
+    }
+
+    fn index(&self, c: &impl Coord) -> Option<usize> {
+        (c.x() < self.w && c.y() < self.h).then(|| c.x() + c.y() * self.w)
+    }
+
+    pub fn contains(&self, c: impl Coord) -> bool {
+        self.index(&c)
+            .is_some_and(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    // returns whether the coordinates were not already in the set,
+    // coordinates outside of the grid are never inserted
+    pub fn insert(&mut self, c: impl Coord) -> bool {
+        let Some(i) = self.index(&c) else {
+            return false;
+        };
+        let word = &mut self.bits[i / 64];
+        let absent = *word & (1 << (i % 64)) == 0;
+        *word |= 1 << (i % 64);
+        absent
+    }
+
+    // returns whether the coordinates were in the set
+    pub fn remove(&mut self, c: impl Coord) -> bool {
+        let Some(i) = self.index(&c) else {
+            return false;
+        };
+        let word = &mut self.bits[i / 64];
+        let present = *word & (1 << (i % 64)) != 0;
+        *word &= !(1 << (i % 64));
+        present
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    // both grids must have the same size
+    pub fn union_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.w, self.h), (other.w, other.h), "grid sizes differ");
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.w, self.h), (other.w, other.h), "grid sizes differ");
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a &= b;
+        }
+    }
+
+    pub fn union(&self, other: &BitGrid) -> BitGrid {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    pub fn intersection(&self, other: &BitGrid) -> BitGrid {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    // the coordinates in the set, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.bits.iter().enumerate().flat_map(move |(n, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let i = n * 64 + word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some((i % self.w, i / self.w))
+            })
+        })
+    }
+}
+
+impl<T> From<&Grid<T>> for BitGrid
+where
+    T: Into<bool> + Copy,
+{
+    fn from(grid: &Grid<T>) -> Self {
+        let mut bits = BitGrid::new(grid.w, grid.h);
+        for (c, &v) in grid.iter_with_coords() {
+            if v.into() {
+                bits.insert(c);
+            }
+        }
+        bits
+    }
+}
+
+// a BitGrid per direction, for sets of (coordinates, direction) states
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DirectionalBitGrid {
+    layers: [BitGrid; 4],
+}
+
+#[allow(unused)]
+impl DirectionalBitGrid {
+    pub fn new(w: usize, h: usize) -> Self {
+        Self {
+            layers: std::array::from_fn(|_| BitGrid::new(w, h)),
+        }
+    }
+
+    pub fn layer(&self, dir: Direction) -> &BitGrid {
+        &self.layers[dir.index()]
+    }
+
+    pub fn contains(&self, c: impl Coord, dir: Direction) -> bool {
+        self.layers[dir.index()].contains(c)
+    }
+
+    pub fn insert(&mut self, c: impl Coord, dir: Direction) -> bool {
+        self.layers[dir.index()].insert(c)
+    }
+
+    pub fn remove(&mut self, c: impl Coord, dir: Direction) -> bool {
+        self.layers[dir.index()].remove(c)
+    }
+
+    pub fn count(&self) -> usize {
+        self.layers.iter().map(BitGrid::count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(BitGrid::is_empty)
+    }
+
+    pub fn clear(&mut self) {
+        self.layers.iter_mut().for_each(BitGrid::clear);
+    }
+
+    pub fn union_with(&mut self, other: &DirectionalBitGrid) {
+        for (a, b) in self.layers.iter_mut().zip(&other.layers) {
+            a.union_with(b);
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &DirectionalBitGrid) {
+        for (a, b) in self.layers.iter_mut().zip(&other.layers) {
+            a.intersect_with(b);
+        }
+    }
+
+    pub fn union(&self, other: &DirectionalBitGrid) -> DirectionalBitGrid {
+        let mut result = self.clone();
+        result.union_with(other);
+        result
+    }
+
+    pub fn intersection(&self, other: &DirectionalBitGrid) -> DirectionalBitGrid {
+        let mut result = self.clone();
+        result.intersect_with(other);
+        result
+    }
+
+    // the coordinates present in at least one direction
+    pub fn positions(&self) -> BitGrid {
+        let mut positions = self.layers[0].clone();
+        for layer in &self.layers[1..] {
+            positions.union_with(layer);
+        }
+        positions
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), Direction)> + '_ {
+        Direction::iter().flat_map(move |d| self.layers[d.index()].iter().map(move |c| (c, d)))
+    }
+}
+
 pub struct ColIter<'a, T> {
     grid: &'a Grid<T>,
     col: usize,
@@ -374,6 +1148,45 @@ impl<'a, T> Iterator for RowIter<'a, T> {
     }
 }
 
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Option<(usize, usize)>,
+    step: (isize, isize),
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos?;
+        let Some(val) = self.grid.get(pos) else {
+            self.pos = None;
+            return None;
+        };
+        self.pos = pos.checked_add_offset(self.step);
+        Some((pos, val))
+    }
+}
+
+pub struct WrappingRay<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Option<(usize, usize)>,
+    from: Option<(usize, usize)>,
+    step: (isize, isize),
+}
+
+impl<'a, T> Iterator for WrappingRay<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.pos.filter(|&p| Some(p) != self.from)?;
+        self.pos = self.grid.wrapping_add_offset(pos, self.step);
+        Some((pos, self.grid.get(pos)?))
+    }
+}
+
 pub struct IterWithCoords<'a, T> {
     grid: &'a Grid<T>,
     x: usize,
@@ -402,3 +1215,287 @@ impl<'a, T> ExactSizeIterator for IterWithCoords<'a, T> {
         self.grid.w * self.grid.h
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapping_neighbours() {
+        let grid = "abc\ndef\nghi".parse::<Grid<u8>>().unwrap();
+
+        let n4 = grid
+            .wrapping_neighbours4((0, 0))
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(n4, b"gbdc");
+        assert_eq!(
+            grid.wrapping_neighbours_coords4((2, 2)).collect::<Vec<_>>(),
+            vec![(2, 1), (0, 2), (2, 0), (1, 2)]
+        );
+
+        let mut n8 = grid
+            .wrapping_neighbours8((0, 0))
+            .copied()
+            .collect::<Vec<_>>();
+        n8.sort();
+        assert_eq!(n8, b"bcdefghi");
+    }
+
+    #[test]
+    fn bit_grid_word_boundaries() {
+        // 8x17 cells need 3 words, indices 63, 64, 127 and 128 are on both sides of a boundary
+        let mut bits = BitGrid::new(8, 17);
+        let boundaries = [(7, 7), (0, 8), (7, 15), (0, 16), (7, 16)];
+        for c in boundaries {
+            assert!(!bits.contains(c));
+            assert!(bits.insert(c));
+            assert!(!bits.insert(c));
+        }
+
+        assert_eq!(bits.count(), 5);
+        assert_eq!(bits.iter().collect::<Vec<_>>(), boundaries);
+        assert!(!bits.contains((6, 7)));
+        assert!(!bits.contains((1, 8)));
+        assert!(!bits.insert((8, 0)));
+        assert!(!bits.contains((0, 17)));
+
+        assert!(bits.remove((0, 8)));
+        assert!(!bits.remove((0, 8)));
+        assert!(bits.contains((7, 7)) && bits.contains((7, 15)));
+        assert_eq!(bits.count(), 4);
+
+        bits.clear();
+        assert!(bits.is_empty());
+    }
+
+    #[test]
+    fn bit_grid_wide_rows() {
+        // a row of 65 cells spans two words
+        let mut bits = BitGrid::new(65, 2);
+        bits.insert((63, 0));
+        bits.insert((64, 0));
+        bits.insert((0, 1));
+
+        assert_eq!(bits.iter().collect::<Vec<_>>(), [(63, 0), (64, 0), (0, 1)]);
+
+        let mut other = BitGrid::new(65, 2);
+        other.insert((64, 0));
+        other.insert((64, 1));
+        assert_eq!(
+            bits.intersection(&other).iter().collect::<Vec<_>>(),
+            [(64, 0)]
+        );
+        assert_eq!(bits.union(&other).count(), 4);
+    }
+
+    #[test]
+    fn grid_parsing() {
+        let grid = "ab\ncd\n\n".parse::<Grid<u8>>().unwrap();
+        assert_eq!((grid.w, grid.h), (2, 2));
+        assert_eq!(grid.as_slice(), b"abcd");
+
+        // CRLF line endings are the same as LF ones
+        let crlf = "ab\r\ncd\r\n".parse::<Grid<u8>>().unwrap();
+        assert_eq!((crlf.w, crlf.h), (2, 2));
+        assert_eq!(crlf.as_slice(), grid.as_slice());
+
+        let numbers = Grid::<u32>::from_split_whitespace_reader("1 2 3\r\n40 5 6".as_bytes());
+        assert_eq!(numbers.unwrap().as_slice(), [1, 2, 3, 40, 5, 6]);
+    }
+
+    #[test]
+    fn grid_errors() {
+        let e = "abc\nde\nfgh".parse::<Grid<u8>>().unwrap_err();
+        assert!(matches!(
+            e,
+            GridError::Ragged {
+                row: 2,
+                width: 2,
+                expected: 3
+            }
+        ));
+        assert_eq!(
+            e.to_string(),
+            "row 2 has 2 cells, expected 3 like the first row"
+        );
+        let e = Grid::<u32>::from_split_whitespace_reader("1 2\n3 4 5".as_bytes()).unwrap_err();
+        assert!(matches!(e, GridError::Ragged { row: 2, .. }));
+
+        assert!(matches!("".parse::<Grid<u8>>(), Err(GridError::Empty)));
+        assert!(matches!(
+            "\n\r\n\n".parse::<Grid<u8>>(),
+            Err(GridError::Empty)
+        ));
+        let empty = ""
+            .parse::<Grid<u8>>()
+            .or_else(GridError::allow_empty)
+            .unwrap();
+        assert_eq!((empty.w, empty.h), (0, 0));
+        assert_eq!(empty.to_string(), "");
+
+        let e = Grid::from_reader_callback("..\n.x".as_bytes(), |b| match b {
+            b'.' => Ok(0),
+            b => Err(char::from(b)),
+        })
+        .unwrap_err();
+        assert!(matches!(
+            e,
+            GridError::Cell {
+                row: 2,
+                col: 2,
+                error: 'x'
+            }
+        ));
+        assert_eq!(e.to_string(), "invalid cell at row 2, column 2: x");
+    }
+
+    #[test]
+    fn from_map_bounds() {
+        // the grid only covers the points, even when none of them is at (0, 0)
+        let points = HashMap::from([(Point::new(3, 5), b'a'), (Point::new(4, 7), b'b')]);
+        let (grid, origin) = Grid::from_map_with_origin(points.clone());
+        assert_eq!(origin, Point::new(3, 5));
+        assert_eq!((grid.w, grid.h), (2, 3));
+        assert_eq!(grid.as_slice(), [b'a', 0, 0, 0, 0, b'b']);
+        assert_eq!(Grid::from_map(points).as_slice(), grid.as_slice());
+
+        let points = HashMap::from([(Point::new(-2, -1), 1u8), (Point::new(0, 0), 2)]);
+        let (grid, origin) = Grid::from_map_with_origin(points);
+        assert_eq!(origin, Point::new(-2, -1));
+        assert_eq!(grid.as_slice(), [1, 0, 0, 0, 0, 2]);
+
+        let (empty, _) = Grid::<u8>::from_map_with_origin(HashMap::new());
+        assert_eq!((empty.w, empty.h), (0, 0));
+    }
+
+    fn cells<'a>(ray: impl Iterator<Item = ((usize, usize), &'a u8)>) -> String {
+        ray.map(|(_, &v)| v as char).collect()
+    }
+
+    #[test]
+    fn rays() {
+        let grid = "abc\ndef\nghi".parse::<Grid<u8>>().unwrap();
+
+        assert_eq!(cells(grid.ray((0, 0), Direction::East)), "bc");
+        assert_eq!(cells(grid.ray((2, 2), (-1, -1))), "ea");
+        assert_eq!(
+            grid.ray((0, 2), (1, -1))
+                .map(|(c, _)| c)
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (2, 0)]
+        );
+
+        // negative steps from the first row or column leave the grid at once
+        assert_eq!(cells(grid.ray((0, 1), Direction::West)), "");
+        assert_eq!(cells(grid.ray((1, 0), Direction::North)), "");
+        assert_eq!(cells(grid.ray((1, 0), (-1, -1))), "");
+
+        // a (0, 0) step never moves away from `from`
+        assert_eq!(cells(grid.ray((1, 1), (0, 0))), "");
+        assert_eq!(cells(grid.wrapping_ray((1, 1), (0, 0))), "");
+    }
+
+    #[test]
+    fn wrapping_rays() {
+        let grid = "abc\ndef\nghi".parse::<Grid<u8>>().unwrap();
+
+        // ends when it comes back to `from`
+        assert_eq!(cells(grid.wrapping_ray((1, 0), Direction::East)), "ca");
+        assert_eq!(cells(grid.wrapping_ray((0, 0), Direction::North)), "gd");
+        assert_eq!(cells(grid.wrapping_ray((0, 0), (1, 1))), "ei");
+        assert_eq!(cells(grid.wrapping_ray((0, 0), (1, 2))), "hf");
+        // steps longer than the grid wrap too
+        assert_eq!(cells(grid.wrapping_ray((0, 0), (4, 0))), "bc");
+        assert_eq!(cells(grid.wrapping_ray((0, 0), (3, 0))), "");
+    }
+
+    #[test]
+    fn rays_until() {
+        let grid = "abcd\nefgh".parse::<Grid<u8>>().unwrap();
+
+        // stops before the matching cell, which is not returned
+        let ray = grid.ray_until((0, 0), Direction::East, |&v| v == b'c');
+        assert_eq!(ray.collect::<Vec<_>>(), vec![((1, 0), &b'b')]);
+
+        assert_eq!(
+            grid.ray_until((0, 0), Direction::East, |&v| v == b'b')
+                .count(),
+            0
+        );
+        // and goes to the edge without a match
+        assert_eq!(
+            grid.ray_until((0, 1), Direction::East, |_| false).count(),
+            3
+        );
+        // `from` itself is not checked
+        assert_eq!(
+            grid.ray_until((0, 0), Direction::South, |&v| v == b'a')
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn point_operators() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 5);
+
+        assert_eq!(a + b, Point::new(2, 3));
+        assert_eq!(a - b, Point::new(4, -7));
+        assert_eq!(a + Direction::North, Point::new(3, -3));
+        assert_eq!(a + Direction8::SouthWest, Point::new(2, -1));
+        assert_eq!(a + (1, 1), Point::new(4, -1));
+        assert_eq!(a * 3, Point::new(9, -6));
+        assert_eq!(-a, Point::new(-3, 2));
+
+        let mut c = a;
+        c += b;
+        c -= Direction::East;
+        assert_eq!(c, Point::new(1, 3));
+
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(a.chebyshev(b), 7);
+        assert_eq!(a.manhattan(a), 0);
+    }
+
+    #[test]
+    fn point_rotations() {
+        // y grows downwards, so a clockwise rotation turns North into East
+        let north = Point::from(Direction::North);
+        assert_eq!(north.rotate_cw(), Point::from(Direction::East));
+        assert_eq!(north.rotate_ccw(), Point::from(Direction::West));
+        assert_eq!(north.rotate_180(), Point::from(Direction::South));
+        for d in Direction::iter() {
+            assert_eq!(Point::from(d).rotate_cw(), Point::from(d.turn_right()));
+        }
+
+        let p = Point::new(2, 1);
+        assert_eq!(p.rotate_cw(), Point::new(-1, 2));
+        assert_eq!(p.rotate_ccw(), Point::new(1, -2));
+        assert_eq!(p.rotate_cw().rotate_ccw(), p);
+        assert_eq!(p.rotate_cw().rotate_cw(), p.rotate_180());
+    }
+
+    #[test]
+    fn point_conversions() {
+        assert_eq!(Point::try_from((2usize, 7usize)).unwrap(), Point::new(2, 7));
+        assert!(Point::try_from((usize::MAX, 0usize)).is_err());
+
+        assert_eq!(
+            <(usize, usize)>::try_from(Point::new(2, 7)).unwrap(),
+            (2, 7)
+        );
+        assert_eq!(
+            <(usize, usize)>::try_from(Point::new(0, 0)).unwrap(),
+            (0, 0)
+        );
+        assert!(<(usize, usize)>::try_from(Point::new(-1, 7)).is_err());
+        assert!(<(usize, usize)>::try_from(Point::new(2, -7)).is_err());
+
+        let origin = Point::new(-3, 4);
+        assert_eq!(Point::from_coords((1, 1), origin), Some(Point::new(-2, 5)));
+        assert_eq!(Point::new(-2, 5).to_coords(origin), Some((1, 1)));
+        assert_eq!(Point::new(-4, 5).to_coords(origin), None);
+    }
+}
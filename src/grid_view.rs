@@ -0,0 +1,336 @@
+use crate::grid::{Coord, Grid};
+
+// A borrowed, possibly rotated, flipped or cropped, view of a grid.
+// The cell (x, y) of the view is the cell origin + x * x_axis + y * y_axis of the grid,
+// so that transformations are composed without copying any cell.
+#[derive(Debug)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (isize, isize),
+    x_axis: (isize, isize),
+    y_axis: (isize, isize),
+    pub w: usize,
+    pub h: usize,
+}
+
+impl<T> Clone for GridView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GridView<'_, T> {}
+
+#[allow(unused)]
+impl<'a, T> GridView<'a, T> {
+    // the coordinates in the grid of a cell of the view
+    pub fn source_coords(&self, c: impl Coord) -> Option<(usize, usize)> {
+        if !self.contains_coord(&c) {
+            return None;
+        }
+
+        let (x, y) = self.corner(c.x() as isize, c.y() as isize);
+        Some((x as usize, y as usize))
+    }
+
+    pub fn contains_coord(&self, c: &impl Coord) -> bool {
+        c.x() < self.w && c.y() < self.h
+    }
+
+    pub fn get(&self, c: impl Coord) -> Option<&'a T> {
+        self.grid.get(self.source_coords(c)?)
+    }
+
+    fn with_axes(
+        &self,
+        origin: (isize, isize),
+        x_axis: (isize, isize),
+        y_axis: (isize, isize),
+        w: usize,
+        h: usize,
+    ) -> Self {
+        Self {
+            grid: self.grid,
+            origin,
+            x_axis,
+            y_axis,
+            w,
+            h,
+        }
+    }
+
+    // the source of the cell at (x, y), which may be outside of the view
+    fn corner(&self, x: isize, y: isize) -> (isize, isize) {
+        (
+            self.origin.0 + x * self.x_axis.0 + y * self.y_axis.0,
+            self.origin.1 + x * self.x_axis.1 + y * self.y_axis.1,
+        )
+    }
+
+    fn neg(axis: (isize, isize)) -> (isize, isize) {
+        (-axis.0, -axis.1)
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        let origin = self.corner(0, self.h as isize - 1);
+        self.with_axes(origin, Self::neg(self.y_axis), self.x_axis, self.h, self.w)
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        let origin = self.corner(self.w as isize - 1, 0);
+        self.with_axes(origin, self.y_axis, Self::neg(self.x_axis), self.h, self.w)
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let origin = self.corner(self.w as isize - 1, self.h as isize - 1);
+        self.with_axes(
+            origin,
+            Self::neg(self.x_axis),
+            Self::neg(self.y_axis),
+            self.w,
+            self.h,
+        )
+    }
+
+    pub fn transpose(&self) -> Self {
+        self.with_axes(self.origin, self.y_axis, self.x_axis, self.h, self.w)
+    }
+
+    // mirrors the columns, the first one becomes the last one
+    pub fn flip_horizontal(&self) -> Self {
+        let origin = self.corner(self.w as isize - 1, 0);
+        self.with_axes(origin, Self::neg(self.x_axis), self.y_axis, self.w, self.h)
+    }
+
+    // mirrors the rows, the first one becomes the last one
+    pub fn flip_vertical(&self) -> Self {
+        let origin = self.corner(0, self.h as isize - 1);
+        self.with_axes(origin, self.x_axis, Self::neg(self.y_axis), self.w, self.h)
+    }
+
+    // the rectangle of w x h cells starting at (x, y), clipped to the view
+    pub fn subgrid(&self, x: usize, y: usize, w: usize, h: usize) -> Self {
+        let x = x.min(self.w);
+        let y = y.min(self.h);
+        let origin = self.corner(x as isize, y as isize);
+        self.with_axes(
+            origin,
+            self.x_axis,
+            self.y_axis,
+            w.min(self.w - x),
+            h.min(self.h - y),
+        )
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.w).flat_map(move |x| view.get((x, y)))
+    }
+
+    pub fn col(&self, x: usize) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..self.h).flat_map(move |y| view.get((x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = &'a T>> {
+        let view = *self;
+        (0..self.h).map(move |y| view.row(y))
+    }
+
+    pub fn iter_with_coords(&self) -> impl Iterator<Item = ((usize, usize), &'a T)> {
+        let view = *self;
+        (0..self.h)
+            .flat_map(move |y| (0..view.w).map(move |x| (x, y)))
+            .flat_map(move |c| Some((c, view.get(c)?)))
+    }
+
+    pub fn to_grid(self) -> Grid<T>
+    where
+        T: Clone + Default,
+    {
+        let mut grid = Grid::new(self.w, self.h);
+        for (c, v) in self.iter_with_coords() {
+            if let Some(cell) = grid.get_mut(c) {
+                *cell = v.clone();
+            }
+        }
+
+        grid
+    }
+}
+
+#[allow(unused)]
+impl<T> Grid<T> {
+    pub fn view(&self) -> GridView<'_, T> {
+        GridView {
+            grid: self,
+            origin: (0, 0),
+            x_axis: (1, 0),
+            y_axis: (0, 1),
+            w: self.w,
+            h: self.h,
+        }
+    }
+
+    pub fn subgrid(&self, x: usize, y: usize, w: usize, h: usize) -> GridView<'_, T> {
+        self.view().subgrid(x, y, w, h)
+    }
+
+    pub fn rotated_cw(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().rotate_cw().to_grid()
+    }
+
+    pub fn rotated_ccw(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().rotate_ccw().to_grid()
+    }
+
+    pub fn rotated_180(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().rotate_180().to_grid()
+    }
+
+    pub fn transposed(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().transpose().to_grid()
+    }
+
+    pub fn flipped_horizontal(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().flip_horizontal().to_grid()
+    }
+
+    pub fn flipped_vertical(&self) -> Self
+    where
+        T: Clone + Default,
+    {
+        self.view().flip_vertical().to_grid()
+    }
+
+    // the ray starting with the cell at start itself
+    fn line_from(
+        &self,
+        start: (usize, usize),
+        step: (isize, isize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.get(start)
+            .map(|v| (start, v))
+            .into_iter()
+            .chain(self.ray(start, step))
+    }
+
+    // the lines going down and right, starting from the bottom left corner
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = ((usize, usize), &T)>> {
+        let starts = (0..self.h)
+            .rev()
+            .map(|y| (0, y))
+            .chain((1..self.w).map(|x| (x, 0)));
+        starts.map(|start| self.line_from(start, (1, 1)))
+    }
+
+    // the lines going down and left, starting from the top left corner
+    pub fn anti_diagonals(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = ((usize, usize), &T)>> {
+        let starts = (0..self.w)
+            .map(|x| (x, 0))
+            .chain((1..self.h).map(|y| (self.w.saturating_sub(1), y)));
+        starts.map(|start| self.line_from(start, (-1, 1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        "abcd\nefgh\nijkl".parse().unwrap()
+    }
+
+    fn text(view: GridView<u8>) -> String {
+        view.rows()
+            .map(|row| row.map(|&v| char::from(v)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn rotations() {
+        let grid = grid();
+        let view = grid.view();
+
+        assert_eq!(text(view.rotate_cw()), "iea\njfb\nkgc\nlhd");
+        assert_eq!(text(view.rotate_ccw()), "dhl\ncgk\nbfj\naei");
+        assert_eq!(text(view.rotate_180()), "lkji\nhgfe\ndcba");
+        assert_eq!(
+            text(view.rotate_cw().rotate_cw().rotate_cw().rotate_cw()),
+            text(view)
+        );
+        assert_eq!(text(view.rotate_cw().rotate_ccw()), text(view));
+        assert_eq!(text(view.rotate_cw().rotate_cw()), text(view.rotate_180()));
+    }
+
+    #[test]
+    fn flips() {
+        let grid = grid();
+        let view = grid.view();
+
+        assert_eq!(text(view.flip_horizontal()), "dcba\nhgfe\nlkji");
+        assert_eq!(text(view.flip_vertical()), "ijkl\nefgh\nabcd");
+        assert_eq!(text(view.flip_horizontal().flip_horizontal()), text(view));
+        assert_eq!(text(view.flip_vertical().flip_vertical()), text(view));
+        assert_eq!(
+            text(view.flip_horizontal().flip_vertical()),
+            text(view.rotate_180())
+        );
+    }
+
+    #[test]
+    fn transpose() {
+        let grid = grid();
+        let view = grid.view();
+
+        assert_eq!(text(view.transpose()), "aei\nbfj\ncgk\ndhl");
+        assert_eq!(text(view.transpose().transpose()), text(view));
+        assert_eq!(
+            text(view.transpose()),
+            text(view.rotate_cw().flip_horizontal())
+        );
+    }
+
+    #[test]
+    fn subgrids() {
+        let grid = grid();
+
+        assert_eq!(text(grid.subgrid(1, 1, 2, 2)), "fg\njk");
+        // clipped to the view
+        assert_eq!(text(grid.subgrid(2, 1, 5, 5)), "gh\nkl");
+        assert_eq!(grid.subgrid(4, 3, 2, 2).w, 0);
+        assert_eq!(grid.subgrid(9, 9, 2, 2).h, 0);
+        // transformations of a subgrid stay in it
+        let sub = grid.subgrid(1, 0, 2, 3);
+        assert_eq!(text(sub.rotate_cw()), "jfb\nkgc");
+        assert_eq!(sub.rotate_cw().source_coords((0, 0)), Some((1, 2)));
+        assert_eq!(sub.get((2, 0)), None);
+    }
+
+    #[test]
+    fn to_grid() {
+        let grid = grid();
+
+        assert_eq!(grid.rotated_cw().rotated_ccw().as_slice(), grid.as_slice());
+        assert_eq!(grid.transposed().as_slice(), b"aeibfjcgkdhl");
+        assert_eq!((grid.transposed().w, grid.transposed().h), (3, 4));
+    }
+}
@@ -0,0 +1,522 @@
+use crate::grid::Grid;
+use crate::render::Color;
+use anyhow::{bail, Context};
+use fnv::FnvHashMap;
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+#[allow(unused)]
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    pub fn gray(level: u8) -> Self {
+        Rgb(level, level, level)
+    }
+}
+
+impl From<Color> for Rgb {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Black => Rgb(0, 0, 0),
+            Color::Red => Rgb(205, 49, 49),
+            Color::Green => Rgb(13, 188, 121),
+            Color::Yellow => Rgb(229, 229, 16),
+            Color::Blue => Rgb(36, 114, 200),
+            Color::Magenta => Rgb(188, 63, 188),
+            Color::Cyan => Rgb(17, 168, 205),
+            Color::White => Rgb(229, 229, 229),
+            Color::Gray => Rgb(102, 102, 102),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub w: usize,
+    pub h: usize,
+    pixels: Vec<Rgb>,
+}
+
+#[allow(unused)]
+impl Image {
+    // each cell becomes a square of scale x scale pixels
+    pub fn from_grid<T>(
+        grid: &Grid<T>,
+        scale: usize,
+        color: impl Fn((usize, usize), &T) -> Rgb,
+    ) -> Self {
+        let scale = scale.max(1);
+        let (w, h) = (grid.w * scale, grid.h * scale);
+        let mut pixels = vec![Rgb::default(); w * h];
+        for ((x, y), v) in grid.iter_with_coords() {
+            let rgb = color((x, y), v);
+            for row in y * scale..(y + 1) * scale {
+                pixels[row * w + x * scale..row * w + (x + 1) * scale].fill(rgb);
+            }
+        }
+
+        Self { w, h, pixels }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.w && y < self.h).then(|| self.pixels[y * self.w + x])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        if x < self.w && y < self.h {
+            self.pixels[y * self.w + x] = rgb;
+        }
+    }
+
+    fn rgb_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|&Rgb(r, g, b)| [r, g, b])
+    }
+
+    // binary PPM (P6)
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.w, self.h).into_bytes();
+        out.extend(self.rgb_bytes());
+        out
+    }
+
+    // 8 bits RGB PNG, without compression
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.h * (1 + self.w * 3));
+        for row in self.pixels.chunks(self.w.max(1)).take(self.h) {
+            // filter type None
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|&Rgb(r, g, b)| [r, g, b]));
+        }
+
+        let mut ihdr = vec![];
+        ihdr.extend((self.w as u32).to_be_bytes());
+        ihdr.extend((self.h as u32).to_be_bytes());
+        // bit depth, color type (RGB), compression, filter, interlace
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    // the format is chosen from the extension, .ppm or .png
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let data = match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => self.to_ppm(),
+            Some("png") => self.to_png(),
+            _ => bail!("unsupported image format, expected .ppm or .png"),
+        };
+        fs::write(path, data).with_context(|| format!("unable to write {}", path.display()))
+    }
+}
+
+// an animated GIF, looping forever, with a palette of at most 256 colors shared by all frames
+pub struct GifEncoder {
+    out: Vec<u8>,
+    w: usize,
+    h: usize,
+    palette: FnvHashMap<Rgb, u8>,
+}
+
+impl GifEncoder {
+    pub fn new(w: usize, h: usize, colors: impl IntoIterator<Item = Rgb>) -> anyhow::Result<Self> {
+        let mut palette = FnvHashMap::default();
+        let mut table = vec![];
+        for rgb in colors {
+            if let Entry::Vacant(e) = palette.entry(rgb) {
+                if table.len() == 256 {
+                    bail!("too many colors for a GIF, at most 256 are supported");
+                }
+                e.insert(table.len() as u8);
+                table.push(rgb);
+            }
+        }
+        if w > u16::MAX as usize || h > u16::MAX as usize {
+            bail!("image too large for a GIF ({w}x{h})");
+        }
+
+        let mut out = b"GIF89a".to_vec();
+        out.extend((w as u16).to_le_bytes());
+        out.extend((h as u16).to_le_bytes());
+        // global color table of 256 entries, background color, aspect ratio
+        out.extend([0xF7, 0, 0]);
+        table.resize(256, Rgb::BLACK);
+        out.extend(table.iter().flat_map(|&Rgb(r, g, b)| [r, g, b]));
+        // loop forever
+        out.extend(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        Ok(Self { out, w, h, palette })
+    }
+
+    // the delay before the next frame is in hundredths of a second
+    pub fn add_frame(&mut self, image: &Image, delay: u16) -> anyhow::Result<()> {
+        if (image.w, image.h) != (self.w, self.h) {
+            bail!(
+                "frame size {}x{} differs from {}x{}",
+                image.w,
+                image.h,
+                self.w,
+                self.h
+            );
+        }
+        let indices = image
+            .pixels
+            .iter()
+            .map(|rgb| self.palette.get(rgb).copied())
+            .collect::<Option<Vec<_>>>()
+            .context("frame color missing from the palette")?;
+
+        // graphic control extension
+        self.out.extend([0x21, 0xF9, 4, 0]);
+        self.out.extend(delay.to_le_bytes());
+        self.out.extend([0, 0]);
+        // image descriptor, without a local color table
+        self.out.extend([0x2C, 0, 0, 0, 0]);
+        self.out.extend((self.w as u16).to_le_bytes());
+        self.out.extend((self.h as u16).to_le_bytes());
+        self.out.push(0);
+
+        self.out.push(8);
+        for block in lzw_encode(&indices, 8).chunks(255) {
+            self.out.push(block.len() as u8);
+            self.out.extend(block);
+        }
+        self.out.push(0);
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.out.push(0x3B);
+        self.out
+    }
+}
+
+// variable width LZW as used by GIF, with codes of at most 12 bits
+fn lzw_encode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODE: u16 = 4096;
+
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut width = min_code_size + 1;
+    let mut next = end + 1;
+    let mut dict = FnvHashMap::<(u16, u8), u16>::default();
+
+    bits.write(clear, width);
+    let mut prefix = None;
+    for &k in data {
+        let Some(p) = prefix else {
+            prefix = Some(k as u16);
+            continue;
+        };
+        if let Some(&code) = dict.get(&(p, k)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        bits.write(p, width);
+        if next < MAX_CODE {
+            dict.insert((p, k), next);
+            next += 1;
+            if next > (1 << width) && width < 12 {
+                width += 1;
+            }
+        } else {
+            // the table is full, start over
+            bits.write(clear, width);
+            dict.clear();
+            width = min_code_size + 1;
+            next = end + 1;
+        }
+        prefix = Some(k as u16);
+    }
+    if let Some(p) = prefix {
+        bits.write(p, width);
+    }
+    bits.write(end, width);
+    bits.finish()
+}
+
+// packs codes starting from the least significant bit
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.acc |= (code as u32) << self.len;
+        self.len += width;
+        while self.len >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+// a zlib stream made of stored (uncompressed) deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xFFFF;
+
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        // an empty final block
+        out.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                0xEDB88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+
+    !data.iter().fold(!0u32, |crc, &b| {
+        table[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    // the sums cannot overflow before 5552 bytes
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::Rng;
+
+    fn random_bytes(len: usize, max: u64) -> Vec<u8> {
+        let mut rng = Rng::new(42);
+        (0..len).map(|_| rng.range(0..max + 1) as u8).collect()
+    }
+
+    // the data of a zlib stream made of stored blocks, and the final flag of each block
+    fn inflate_stored(stream: &[u8]) -> (Vec<u8>, Vec<bool>) {
+        assert_eq!(u16::from_be_bytes([stream[0], stream[1]]) % 31, 0);
+        let mut data = vec![];
+        let mut finals = vec![];
+        let mut pos = 2;
+        loop {
+            let header = stream[pos];
+            assert_eq!(header & 0b110, 0, "not a stored block");
+            let len = u16::from_le_bytes([stream[pos + 1], stream[pos + 2]]);
+            let nlen = u16::from_le_bytes([stream[pos + 3], stream[pos + 4]]);
+            assert_eq!(len, !nlen);
+            data.extend(&stream[pos + 5..pos + 5 + len as usize]);
+            pos += 5 + len as usize;
+            finals.push(header & 1 == 1);
+            if header & 1 == 1 {
+                break;
+            }
+        }
+        assert_eq!(stream[pos..], adler32(&data).to_be_bytes());
+        (data, finals)
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xAE426082);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+        assert_eq!(adler32(b""), 1);
+
+        // long enough to need the intermediate modulo
+        let data = random_bytes(20000, 255);
+        let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &v| {
+            let a = (a + v as u32) % 65521;
+            (a, (b + a) % 65521)
+        });
+        assert_eq!(adler32(&data), (b << 16) | a);
+    }
+
+    #[test]
+    fn zlib_empty() {
+        assert_eq!(
+            zlib_stored(&[]),
+            [0x78, 0x01, 1, 0, 0, 0xFF, 0xFF, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn zlib_blocks() {
+        let (data, finals) = inflate_stored(&zlib_stored(b"hello"));
+        assert_eq!(data, b"hello");
+        assert_eq!(finals, [true]);
+
+        // more than the 0xFFFF bytes of a block
+        let long = random_bytes(2 * 0xFFFF + 10, 255);
+        let (data, finals) = inflate_stored(&zlib_stored(&long));
+        assert_eq!(data, long);
+        assert_eq!(finals, [false, false, true]);
+
+        let exact = random_bytes(0xFFFF, 255);
+        let (data, finals) = inflate_stored(&zlib_stored(&exact));
+        assert_eq!(data, exact);
+        assert_eq!(finals, [true]);
+    }
+
+    #[test]
+    fn png() {
+        let mut image = Image::from_grid(&Grid::new_with(2, 1, ()), 1, |_, _| Rgb::WHITE);
+        image.set(1, 0, Rgb(1, 2, 3));
+        let png = image.to_png();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let mut chunks = vec![];
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+            chunks.push((&body[..4], &body[4..]));
+            pos += 12 + len;
+        }
+
+        let kinds = chunks.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        let (raw, _) = inflate_stored(chunks[1].1);
+        assert_eq!(raw, [0, 255, 255, 255, 1, 2, 3]);
+    }
+
+    // a GIF LZW decoder, also returns the number of clear codes
+    fn lzw_decode(data: &[u8], min_code_size: u8) -> (Vec<u8>, usize) {
+        let clear = 1 << min_code_size;
+        let end = clear + 1;
+        let initial = (0..clear)
+            .map(|v| vec![v as u8])
+            .chain([vec![], vec![]])
+            .collect::<Vec<_>>();
+
+        let mut table = initial.clone();
+        let mut width = min_code_size + 1;
+        let mut prev: Option<usize> = None;
+        let mut out = vec![];
+        let mut clears = 0;
+        let mut pos = 0;
+        loop {
+            let code = (0..width as usize)
+                .map(|i| ((data[(pos + i) / 8] >> ((pos + i) % 8)) & 1) as usize)
+                .enumerate()
+                .map(|(i, bit)| bit << i)
+                .sum::<usize>();
+            pos += width as usize;
+
+            if code == clear {
+                table = initial.clone();
+                width = min_code_size + 1;
+                prev = None;
+                clears += 1;
+                continue;
+            }
+            if code == end {
+                return (out, clears);
+            }
+
+            let entry = match (table.get(code), prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) if code == table.len() => {
+                    [table[p].as_slice(), &table[p][..1]].concat()
+                }
+                _ => panic!("invalid code {code}"),
+            };
+            out.extend(&entry);
+            if let Some(p) = prev {
+                if table.len() < 4096 {
+                    table.push([table[p].as_slice(), &entry[..1]].concat());
+                }
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            prev = Some(code);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        for data in [
+            vec![],
+            vec![7],
+            vec![0; 1000],
+            b"TOBEORNOTTOBEORTOBEORNOT".to_vec(),
+        ] {
+            assert_eq!(lzw_decode(&lzw_encode(&data, 8), 8), (data, 1));
+        }
+
+        let small_palette = random_bytes(5000, 3);
+        assert_eq!(
+            lzw_decode(&lzw_encode(&small_palette, 2), 2).0,
+            small_palette
+        );
+    }
+
+    #[test]
+    fn lzw_full_table() {
+        // random bytes barely compress, each of them fills the table a bit more
+        let data = random_bytes(20000, 255);
+        let (decoded, clears) = lzw_decode(&lzw_encode(&data, 8), 8);
+        assert_eq!(decoded, data);
+        assert!(clears > 1, "the table was never reset");
+    }
+}
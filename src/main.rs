@@ -1,38 +1,207 @@
-use crate::solutions::{exec_all_days, exec_day};
+use crate::animation::AnimationOutput;
+use crate::generator::GenParams;
+use crate::scaffold::Template;
+use crate::solutions::{animate_day, exec_all_days, exec_day, gen_day, image_day, render_day};
+use anyhow::{anyhow, bail, Context};
 use humantime::format_duration;
 use std::env;
+use std::io::{self, Write};
 use std::time::Duration;
 
+mod animation;
+mod client;
+mod config;
+mod generator;
 mod grid;
+mod grid_view;
+mod image;
+mod padded_grid;
 mod parsing;
+mod region;
+mod render;
+mod scaffold;
+mod search;
 mod solutions;
 mod solver;
+mod sparse_grid;
+mod submit;
+mod vault;
 
-fn main() {
-    if let Some(day) = env::args().nth(1) {
-        if let Some(o) = exec_day(day.parse().unwrap_or(1)) {
-            println!("{o}");
-        } else {
-            eprintln!("Day {day} not found");
-        }
-    } else {
-        let outputs = exec_all_days();
-        for (i, o) in outputs.iter().enumerate() {
-            println!("=== Day {} ===\n{o}", i + 1);
-        }
-
-        println!("=== Global stats ===");
-        let total_parsed = outputs
-            .iter()
-            .map(|o| o.timings.parse_duration)
-            .sum::<Duration>();
-        let total_solved = outputs
-            .iter()
-            .map(|o| o.timings.part1_duration + o.timings.part2_duration)
-            .sum::<Duration>();
-
-        println!("Parsing: {}", format_duration(total_parsed));
-        println!("Solving: {}", format_duration(total_solved));
-        println!("Total:   {}", format_duration(total_parsed + total_solved));
+const USAGE: &str = "usage: aoc [<day>] | aoc <command> [<args>...]
+commands: new, fetch, submit, vault, gen, render, image, animate";
+
+fn main() -> anyhow::Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("new") => new_day(&args[1..])?,
+        Some("fetch") => {
+            let day = args.get(1).context("usage: aoc fetch <day>")?;
+            client::fetch(day.parse().context("invalid day")?)?;
+        }
+        Some("submit") => {
+            const USAGE: &str = "usage: aoc submit <day> <part>";
+            let day = args.get(1).context(USAGE)?;
+            let part = args.get(2).context(USAGE)?;
+            submit::submit(
+                day.parse().context("invalid day")?,
+                part.parse().context("invalid part")?,
+            )?;
+        }
+        Some("vault") => vault_command(&args[1..])?,
+        Some("gen") => gen(&args[1..])?,
+        Some("render") => {
+            let day = args.get(1).context("usage: aoc render <day>")?;
+            let day = day.parse().context("invalid day")?;
+            let rendered = render_day(day).ok_or_else(|| anyhow!("day {day} not found"))??;
+            print!("{rendered}");
+        }
+        Some("image") => image(&args[1..])?,
+        Some("animate") => animate(&args[1..])?,
+        Some(arg) => {
+            let Ok(day) = arg.parse() else {
+                bail!("unknown command {arg}\n{USAGE}");
+            };
+            run_day(day);
+        }
+        None => run_all_days(),
+    }
+
+    Ok(())
+}
+
+fn new_day(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: aoc new <day> [--template <name>]";
+
+    let day = args.first().context(USAGE)?;
+    let template = match args.get(1).map(String::as_str) {
+        Some("--template") => args.get(2).context(USAGE)?.parse()?,
+        Some(_) => bail!(USAGE),
+        None => Template::default(),
+    };
+
+    scaffold::new_day(day.parse().context("invalid day")?, template)
+}
+
+fn vault_command(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: aoc vault add <day> [<file>|-] | aoc vault extract <day>";
+
+    let day = args.get(1).context(USAGE)?.parse().context("invalid day")?;
+    match args.first().map(String::as_str) {
+        Some("add") => {
+            let path = vault::add(day, args.get(2).map(String::as_str))?;
+            println!("stored {}", path.display());
+        }
+        // the plaintext is only ever written to stdout
+        Some("extract") => io::stdout().write_all(&vault::extract(day)?)?,
+        _ => bail!(USAGE),
+    }
+
+    Ok(())
+}
+
+fn gen(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: aoc gen <day> [--size <n>] [--seed <n>] [--density <p>]";
+
+    let day = args.first().context(USAGE)?;
+    let day = day.parse().context("invalid day")?;
+    let mut params = GenParams::default();
+    let mut seed = 0;
+    for option in args[1..].chunks(2) {
+        match option {
+            [name, value] if name == "--size" => {
+                params.size = value.parse().context("invalid size")?
+            }
+            [name, value] if name == "--seed" => seed = value.parse().context("invalid seed")?,
+            [name, value] if name == "--density" => {
+                params.density = value.parse().context("invalid density")?
+            }
+            _ => bail!(USAGE),
+        }
+    }
+    if params.size == 0 {
+        bail!("invalid size, expected a positive value");
     }
+
+    let input = gen_day(day, &params, seed).ok_or_else(|| anyhow!("day {day} not found"))??;
+    print!("{input}");
+
+    Ok(())
+}
+
+fn image(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: aoc image <day> <file.png|file.ppm> [--scale <n>]";
+
+    let day = args.first().context(USAGE)?;
+    let day = day.parse().context("invalid day")?;
+    let file = args.get(1).context(USAGE)?;
+    let scale = match &args[2..] {
+        [] => 4,
+        [name, value] if name == "--scale" => value.parse().context("invalid scale")?,
+        _ => bail!(USAGE),
+    };
+
+    let image = image_day(day, scale).ok_or_else(|| anyhow!("day {day} not found"))??;
+    image.save(file)?;
+    println!("{}x{} image written to {file}", image.w, image.h);
+
+    Ok(())
+}
+
+fn animate(args: &[String]) -> anyhow::Result<()> {
+    const USAGE: &str = "usage: aoc animate <day> [--out <file.gif|dir>] [--fps <n>] [--scale <n>]";
+
+    let day = args.first().context(USAGE)?;
+    let day = day.parse().context("invalid day")?;
+    let mut output = AnimationOutput::default();
+    for option in args[1..].chunks(2) {
+        match option {
+            [name, value] if name == "--out" => output.target = Some(value.into()),
+            [name, value] if name == "--fps" => {
+                output.fps = value.parse().context("invalid fps")?
+            }
+            [name, value] if name == "--scale" => {
+                output.scale = value.parse().context("invalid scale")?
+            }
+            _ => bail!(USAGE),
+        }
+    }
+
+    animate_day(day, &output).ok_or_else(|| anyhow!("day {day} not found"))?
+}
+
+fn run_day(day: u32) {
+    match exec_day(day) {
+        Some(Ok(o)) => println!("{o}"),
+        Some(Err(e)) => eprintln!("Day {day}: {e:#}"),
+        None => eprintln!("Day {day} not found"),
+    }
+}
+
+fn run_all_days() {
+    let mut outputs = vec![];
+    for (day, o) in exec_all_days() {
+        println!("=== Day {day} ===");
+        match o {
+            Ok(o) => {
+                println!("{o}");
+                outputs.push(o);
+            }
+            Err(e) => println!("{e:#}"),
+        }
+    }
+
+    println!("=== Global stats ===");
+    let total_parsed = outputs
+        .iter()
+        .map(|o| o.timings.parse_duration)
+        .sum::<Duration>();
+    let total_solved = outputs
+        .iter()
+        .map(|o| o.timings.part1_duration + o.timings.part2_duration)
+        .sum::<Duration>();
+
+    println!("Parsing: {}", format_duration(total_parsed));
+    println!("Solving: {}", format_duration(total_solved));
+    println!("Total:   {}", format_duration(total_parsed + total_solved));
 }
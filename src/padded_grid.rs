@@ -0,0 +1,207 @@
+use crate::grid::{Coord, Grid};
+use std::ops::{Index, IndexMut};
+
+// a grid surrounded by a border of pad sentinel cells, addressed by linear indices:
+// from any cell of the grid, moving by at most pad cells in each direction lands on a
+// cell of the grid or of the border, so neighbours need no bounds checks
+#[derive(Clone, Debug)]
+pub struct PaddedGrid<T> {
+    cells: Vec<T>,
+    pub w: usize,
+    pub h: usize,
+    pad: usize,
+    stride: usize,
+}
+
+#[allow(unused)]
+impl<T> PaddedGrid<T> {
+    // panics if pad is 0, without a border the offsets would wrap to the next or previous row
+    pub fn from_grid(grid: &Grid<T>, pad: usize, sentinel: T) -> Self
+    where
+        T: Clone,
+    {
+        assert!(pad > 0, "the border must be at least one cell wide");
+        let stride = grid.w + 2 * pad;
+        let mut cells = vec![sentinel; stride * (grid.h + 2 * pad)];
+        for ((x, y), v) in grid.iter_with_coords() {
+            cells[(y + pad) * stride + x + pad] = v.clone();
+        }
+
+        Self {
+            cells,
+            w: grid.w,
+            h: grid.h,
+            pad,
+            stride,
+        }
+    }
+
+    // the grid without its border
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.h)
+            .flat_map(|y| {
+                let start = (y + self.pad) * self.stride + self.pad;
+                self.cells[start..start + self.w].iter().cloned()
+            })
+            .collect();
+        Grid::from_cells(cells, self.w, self.h).expect("inner cells have the grid size")
+    }
+
+    pub fn pad(&self) -> usize {
+        self.pad
+    }
+
+    // the distance between two vertically adjacent cells
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    // the number of cells, border included
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // the index of the cell at grid coordinates, None outside of the grid
+    pub fn index(&self, c: impl Coord) -> Option<usize> {
+        (c.x() < self.w && c.y() < self.h)
+            .then(|| (c.y() + self.pad) * self.stride + c.x() + self.pad)
+    }
+
+    // the grid coordinates of an index, None for the border
+    pub fn coords(&self, idx: usize) -> Option<(usize, usize)> {
+        let (x, y) = (idx % self.stride, idx / self.stride);
+        let c = (x.checked_sub(self.pad)?, y.checked_sub(self.pad)?);
+        (c.0 < self.w && c.1 < self.h).then_some(c)
+    }
+
+    // the change of index for a move, which must be at most pad cells in each direction
+    pub fn offset(&self, step: impl Into<(isize, isize)>) -> isize {
+        let (dx, dy) = step.into();
+        debug_assert!(dx.unsigned_abs() <= self.pad && dy.unsigned_abs() <= self.pad);
+        dx + dy * self.stride as isize
+    }
+
+    // North, East, South, West, these moves need a border of at least one cell,
+    // which from_grid guarantees
+    pub fn offsets4(&self) -> [isize; 4] {
+        let s = self.stride as isize;
+        [-s, 1, s, -1]
+    }
+
+    // clockwise from North, with the same requirement as offsets4
+    pub fn offsets8(&self) -> [isize; 8] {
+        let s = self.stride as isize;
+        [-s, 1 - s, 1, 1 + s, s, s - 1, -1, -1 - s]
+    }
+
+    // only valid from an index of the grid, not of the border
+    #[inline]
+    pub fn neighbour(&self, idx: usize, offset: isize) -> usize {
+        idx.wrapping_add_signed(offset)
+    }
+
+    pub fn get(&self, c: impl Coord) -> Option<&T> {
+        self.index(c).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, c: impl Coord) -> Option<&mut T> {
+        self.index(c).map(|i| &mut self.cells[i])
+    }
+
+    // the indices of the cells of the grid, row by row
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.h).flat_map(move |y| {
+            let start = (y + self.pad) * self.stride + self.pad;
+            start..start + self.w
+        })
+    }
+}
+
+impl<T> Index<usize> for PaddedGrid<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &Self::Output {
+        &self.cells[idx]
+    }
+}
+
+impl<T> IndexMut<usize> for PaddedGrid<T> {
+    #[inline]
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        &mut self.cells[idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded() -> PaddedGrid<u8> {
+        let grid = "abc\ndef".parse::<Grid<u8>>().unwrap();
+        PaddedGrid::from_grid(&grid, 1, b'#')
+    }
+
+    #[test]
+    fn round_trip() {
+        let grid = "abc\ndef".parse::<Grid<u8>>().unwrap();
+        let lab = PaddedGrid::from_grid(&grid, 2, b'#');
+
+        assert_eq!((lab.w, lab.h, lab.stride(), lab.len()), (3, 2, 7, 42));
+        assert_eq!(lab.to_grid().as_slice(), grid.as_slice());
+        assert_eq!(lab.indices().map(|i| lab[i]).collect::<Vec<_>>(), b"abcdef");
+    }
+
+    #[test]
+    fn indices_and_coords() {
+        let lab = padded();
+
+        assert_eq!(lab.index((0, 0)), Some(6));
+        assert_eq!(lab.index((2, 1)), Some(13));
+        assert_eq!(lab.index((3, 0)), None);
+        assert_eq!(lab.index((0, 2)), None);
+        for (c, _) in lab.to_grid().iter_with_coords() {
+            assert_eq!(lab.index(c).and_then(|i| lab.coords(i)), Some(c));
+        }
+        // the border
+        for i in [0, 4, 5, 9, 10, 14, 15, 19] {
+            assert_eq!(lab.coords(i), None);
+            assert_eq!(lab[i], b'#');
+        }
+        assert_eq!(lab.get((1, 1)), Some(&b'e'));
+    }
+
+    #[test]
+    fn neighbours_at_corners() {
+        let lab = padded();
+        let around = |c: (usize, usize), offsets: &[isize]| {
+            let idx = lab.index(c).unwrap();
+            offsets
+                .iter()
+                .map(|&o| char::from(lab[lab.neighbour(idx, o)]))
+                .collect::<String>()
+        };
+
+        assert_eq!(around((0, 0), &lab.offsets4()), "#bd#");
+        assert_eq!(around((2, 0), &lab.offsets4()), "##fb");
+        assert_eq!(around((0, 1), &lab.offsets4()), "ae##");
+        assert_eq!(around((2, 1), &lab.offsets4()), "c##e");
+        assert_eq!(around((0, 0), &lab.offsets8()), "##bed###");
+        assert_eq!(around((2, 1), &lab.offsets8()), "c#####eb");
+        assert_eq!(lab.offset((1, -1)), lab.offsets8()[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one cell")]
+    fn no_border() {
+        let grid = "ab\ncd".parse::<Grid<u8>>().unwrap();
+        PaddedGrid::from_grid(&grid, 0, b'#');
+    }
+}
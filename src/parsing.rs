@@ -29,6 +29,7 @@ where
     }
 }
 
+#[cfg_attr(aoc_days_subset, allow(dead_code))]
 pub trait BufReadExt<T> {
     fn split_by<B: FromIterator<T>>(self, separator: u8) -> B;
     fn split_commas<B: FromIterator<T>>(self) -> B;
@@ -70,6 +71,7 @@ where
     }
 }
 
+#[cfg_attr(aoc_days_subset, allow(dead_code))]
 pub trait ReadAll {
     fn read_all(self) -> String;
 }
@@ -0,0 +1,292 @@
+use crate::grid::{Coord, Direction, Grid};
+use crate::search::bfs;
+use fnv::FnvHashSet;
+use itertools::Either;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    // the connectivity of the cells outside of a region, used to find its holes
+    fn dual(&self) -> Self {
+        match self {
+            Connectivity::Four => Connectivity::Eight,
+            Connectivity::Eight => Connectivity::Four,
+        }
+    }
+
+    fn neighbours<'a, T>(
+        &self,
+        grid: &'a Grid<T>,
+        c: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        match self {
+            Connectivity::Four => Either::Left(grid.iter_neighbours_coords4(c)),
+            Connectivity::Eight => Either::Right(grid.iter_neighbours_coords8(c)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub cells: FnvHashSet<(usize, usize)>,
+    connectivity: Connectivity,
+}
+
+#[allow(unused)]
+impl Region {
+    pub fn contains(&self, c: &impl Coord) -> bool {
+        self.cells.contains(&c.coords())
+    }
+
+    fn contains_signed(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && self.cells.contains(&(x as usize, y as usize))
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    // number of cell edges between the region and the outside
+    pub fn perimeter(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|&(x, y)| {
+                Direction::iter()
+                    .filter(|d| !self.contains_signed(x as isize + d.dx(), y as isize + d.dy()))
+                    .count()
+            })
+            .sum()
+    }
+
+    // number of straight sides of the outline, which is also its number of corners
+    pub fn sides(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|&(x, y)| {
+                let (x, y) = (x as isize, y as isize);
+                [(-1, -1), (1, -1), (1, 1), (-1, 1)]
+                    .iter()
+                    .filter(|&&(dx, dy)| {
+                        let horizontal = self.contains_signed(x + dx, y);
+                        let vertical = self.contains_signed(x, y + dy);
+                        let diagonal = self.contains_signed(x + dx, y + dy);
+                        // convex or concave corner
+                        (!horizontal && !vertical) || (horizontal && vertical && !diagonal)
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    // the top left and bottom right cells of the smallest rectangle holding the region,
+    // None for an empty region
+    pub fn bounding_box(&self) -> Option<((usize, usize), (usize, usize))> {
+        let min_x = self.cells.iter().map(|c| c.0).min()?;
+        let max_x = self.cells.iter().map(|c| c.0).max()?;
+        let min_y = self.cells.iter().map(|c| c.1).min()?;
+        let max_y = self.cells.iter().map(|c| c.1).max()?;
+
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+
+    // number of groups of outside cells completely enclosed by the region
+    pub fn holes(&self) -> usize {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounding_box() else {
+            return 0;
+        };
+
+        // mask of the bounding box with a 1 cell margin, so that the outside is connected
+        let mut mask = Grid::new_with(max_x - min_x + 3, max_y - min_y + 3, false);
+        for &(x, y) in &self.cells {
+            if let Some(v) = mask.get_mut((x - min_x + 1, y - min_y + 1)) {
+                *v = true;
+            }
+        }
+
+        let outside = self.connectivity.dual();
+        let successors = |c: &(usize, usize)| {
+            outside
+                .neighbours(&mask, *c)
+                .filter(|&n| mask.get(n) == Some(&false))
+        };
+
+        let mut seen = bfs([(0, 0)], successors, |_| false)
+            .dist
+            .into_keys()
+            .collect::<FnvHashSet<_>>();
+        let mut holes = 0;
+        for (c, &v) in mask.iter_with_coords() {
+            if !v && !seen.contains(&c) {
+                holes += 1;
+                seen.extend(bfs([c], successors, |_| false).dist.into_keys());
+            }
+        }
+
+        holes
+    }
+}
+
+// the region holding start, made of the cells connected through pairs of neighbours
+// for which same returns true
+#[allow(unused)]
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+    connectivity: Connectivity,
+    same: impl Fn(&T, &T) -> bool,
+) -> Region {
+    let same = &same;
+    let successors = |&c: &(usize, usize)| {
+        let v = grid.get(c);
+        connectivity
+            .neighbours(grid, c)
+            .filter(move |&n| v.zip(grid.get(n)).is_some_and(|(a, b)| same(a, b)))
+    };
+
+    let cells = if grid.contains_coord(&start) {
+        bfs([start], successors, |_| false)
+            .dist
+            .into_keys()
+            .collect()
+    } else {
+        FnvHashSet::default()
+    };
+
+    Region {
+        cells,
+        connectivity,
+    }
+}
+
+// splits the grid into regions, also returns the index of the region of each cell
+#[allow(unused)]
+pub fn label_regions<T>(
+    grid: &Grid<T>,
+    connectivity: Connectivity,
+    same: impl Fn(&T, &T) -> bool,
+) -> (Grid<usize>, Vec<Region>) {
+    let mut labels = Grid::new_with(grid.w, grid.h, usize::MAX);
+    let mut regions = vec![];
+
+    for (c, _) in grid.iter_with_coords() {
+        if labels.get(c) != Some(&usize::MAX) {
+            continue;
+        }
+
+        let region = flood_fill(grid, c, connectivity, &same);
+        for &cell in &region.cells {
+            if let Some(l) = labels.get_mut(cell) {
+                *l = regions.len();
+            }
+        }
+        regions.push(region);
+    }
+
+    (labels, regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regions(s: &str) -> Vec<Region> {
+        let grid = s.parse::<Grid<u8>>().unwrap();
+        label_regions(&grid, Connectivity::Four, |a, b| a == b).1
+    }
+
+    fn price(s: &str) -> usize {
+        regions(s).iter().map(|r| r.area() * r.perimeter()).sum()
+    }
+
+    fn discounted_price(s: &str) -> usize {
+        regions(s).iter().map(|r| r.area() * r.sides()).sum()
+    }
+
+    const SMALL: &str = "AAAA\nBBCD\nBBCC\nEEEC";
+    const HOLES: &str = "OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO";
+    const E_SHAPE: &str = "EEEEE\nEXXXX\nEEEEE\nEXXXX\nEEEEE";
+    const DIAGONAL: &str = "AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA";
+
+    #[test]
+    fn perimeters() {
+        assert_eq!(price(SMALL), 140);
+        assert_eq!(price(HOLES), 772);
+    }
+
+    #[test]
+    fn sides() {
+        assert_eq!(discounted_price(SMALL), 80);
+        assert_eq!(discounted_price(HOLES), 436);
+        assert_eq!(discounted_price(E_SHAPE), 236);
+        assert_eq!(discounted_price(DIAGONAL), 368);
+    }
+
+    #[test]
+    fn holes() {
+        let regions = regions(HOLES);
+        let outer = regions.iter().find(|r| r.contains(&(0, 0))).unwrap();
+        assert_eq!(outer.holes(), 4);
+        assert!(regions
+            .iter()
+            .filter(|r| !r.contains(&(0, 0)))
+            .all(|r| r.holes() == 0));
+
+        // the B squares touch diagonally, they are a single hole for 4-connected regions
+        // and two holes for 8-connected ones
+        let grid = DIAGONAL.parse::<Grid<u8>>().unwrap();
+        let same = |a: &u8, b: &u8| a == b;
+        assert_eq!(
+            flood_fill(&grid, (0, 0), Connectivity::Four, same).holes(),
+            1
+        );
+        assert_eq!(
+            flood_fill(&grid, (0, 0), Connectivity::Eight, same).holes(),
+            2
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let grid = SMALL.parse::<Grid<u8>>().unwrap();
+        let (labels, regions) = label_regions(&grid, Connectivity::Four, |a, b| a == b);
+
+        assert_eq!(regions.len(), 5);
+        assert_eq!(labels.get((0, 1)), labels.get((1, 2)));
+        assert_ne!(labels.get((2, 1)), labels.get((3, 1)));
+        for (c, &l) in labels.iter_with_coords() {
+            assert!(regions[l].contains(&c));
+        }
+    }
+
+    #[test]
+    fn eight_connectivity() {
+        let grid = "X.X\n.X.\nX.X".parse::<Grid<u8>>().unwrap();
+
+        assert_eq!(
+            flood_fill(&grid, (0, 0), Connectivity::Four, |a, b| a == b).area(),
+            1
+        );
+        assert_eq!(
+            flood_fill(&grid, (0, 0), Connectivity::Eight, |a, b| a == b).area(),
+            5
+        );
+        let (_, regions) = label_regions(&grid, Connectivity::Eight, |a, b| a == b);
+        assert_eq!(regions.len(), 2);
+    }
+
+    #[test]
+    fn bounding_box() {
+        let grid = E_SHAPE.parse::<Grid<u8>>().unwrap();
+        let x = flood_fill(&grid, (1, 1), Connectivity::Four, |a, b| a == b);
+        assert_eq!(x.bounding_box(), Some(((1, 1), (4, 1))));
+
+        let outside = flood_fill(&grid, (5, 0), Connectivity::Four, |a, b| a == b);
+        assert_eq!(outside.area(), 0);
+        assert_eq!(outside.bounding_box(), None);
+        assert_eq!(outside.holes(), 0);
+    }
+}
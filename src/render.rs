@@ -0,0 +1,190 @@
+use crate::grid::{Coord, Direction, Grid};
+use fnv::FnvHashMap;
+use std::env;
+use std::fmt::Write;
+use std::io::{stdout, IsTerminal};
+
+#[allow(unused)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Gray => 90,
+        }
+    }
+}
+
+// whether stdout should receive ANSI escapes, see https://no-color.org
+pub fn use_colors() -> bool {
+    stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+type CellColors<'a, T> = Box<dyn Fn((usize, usize), &T) -> Option<Color> + 'a>;
+
+struct Layer {
+    name: String,
+    // shown in the legend, None for layers that only change the color of cells
+    symbol: Option<char>,
+    color: Color,
+    cells: FnvHashMap<(usize, usize), Option<char>>,
+}
+
+// paints overlays on top of a grid, the last added layer wins when layers overlap
+pub struct Renderer<'a, T> {
+    grid: &'a Grid<T>,
+    glyph: Box<dyn Fn(&T) -> char + 'a>,
+    colors: Option<CellColors<'a, T>>,
+    layers: Vec<Layer>,
+    ansi: bool,
+}
+
+#[allow(unused)]
+impl<'a, T> Renderer<'a, T> {
+    pub fn new(grid: &'a Grid<T>, glyph: impl Fn(&T) -> char + 'a) -> Self {
+        Self {
+            grid,
+            glyph: Box::new(glyph),
+            colors: None,
+            layers: vec![],
+            ansi: use_colors(),
+        }
+    }
+
+    // forces colors on or off instead of detecting a terminal
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    // the color of each cell of the grid, below all the layers
+    pub fn colors(mut self, colors: impl Fn((usize, usize), &T) -> Option<Color> + 'a) -> Self {
+        self.colors = Some(Box::new(colors));
+        self
+    }
+
+    // replaces the cells with the glyph
+    pub fn mark(
+        mut self,
+        name: &str,
+        coords: impl IntoIterator<Item = impl Coord>,
+        glyph: char,
+        color: Color,
+    ) -> Self {
+        self.layers.push(Layer {
+            name: name.to_string(),
+            symbol: Some(glyph),
+            color,
+            cells: coords
+                .into_iter()
+                .map(|c| (c.coords(), Some(glyph)))
+                .collect(),
+        });
+        self
+    }
+
+    // only changes the color of the cells, so it is invisible without colors
+    pub fn highlight(
+        mut self,
+        name: &str,
+        coords: impl IntoIterator<Item = impl Coord>,
+        color: Color,
+    ) -> Self {
+        self.layers.push(Layer {
+            name: name.to_string(),
+            symbol: None,
+            color,
+            cells: coords.into_iter().map(|c| (c.coords(), None)).collect(),
+        });
+        self
+    }
+
+    // draws an arrow on each cell toward the next one, steps that are not
+    // to a neighbour only change the color of the cell
+    pub fn path(
+        mut self,
+        name: &str,
+        coords: impl IntoIterator<Item = impl Coord>,
+        color: Color,
+    ) -> Self {
+        let coords = coords.into_iter().map(|c| c.coords()).collect::<Vec<_>>();
+        let mut cells = FnvHashMap::default();
+        for step in coords.windows(2) {
+            let arrow = Direction::from_offset(step[1].diff(&step[0])).map(|d| d.arrow());
+            cells.insert(step[0], arrow);
+        }
+        if let Some(&last) = coords.last() {
+            cells.entry(last).or_insert(None);
+        }
+
+        self.layers.push(Layer {
+            name: name.to_string(),
+            symbol: Some('>'),
+            color,
+            cells,
+        });
+        self
+    }
+
+    fn paint(&self, out: &mut String, c: char, color: Option<Color>) {
+        match color {
+            Some(color) if self.ansi => {
+                _ = write!(out, "\x1b[{}m{c}\x1b[0m", color.ansi_code());
+            }
+            _ => out.push(c),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (c, v) in self.grid.iter_with_coords() {
+            let mut glyph = (self.glyph)(v);
+            let mut color = self.colors.as_ref().and_then(|colors| colors(c, v));
+            for layer in &self.layers {
+                if let Some(g) = layer.cells.get(&c) {
+                    glyph = g.unwrap_or(glyph);
+                    color = Some(layer.color);
+                }
+            }
+            self.paint(&mut out, glyph, color);
+            if c.0 + 1 == self.grid.w {
+                out.push('\n');
+            }
+        }
+
+        // the layers that are not visible without colors are left out of the legend
+        let legend = self
+            .layers
+            .iter()
+            .filter(|l| self.ansi || l.symbol.is_some())
+            .collect::<Vec<_>>();
+        if !legend.is_empty() {
+            out.push('\n');
+        }
+        for layer in legend {
+            self.paint(&mut out, layer.symbol.unwrap_or('█'), Some(layer.color));
+            _ = writeln!(out, " {} ({})", layer.name, layer.cells.len());
+        }
+
+        out
+    }
+}
@@ -0,0 +1,237 @@
+use crate::solver::{example_file, input_file};
+use anyhow::{anyhow, bail, Context};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+const TESTS_TEMPLATE: &str = include_str!("../templates/tests.rs.template");
+const REGISTRY_TEMPLATE: &str = include_str!("../templates/mod.rs.template");
+const SOLUTIONS_DIR: &str = "src/solutions";
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Template {
+    #[default]
+    Blank,
+    Grid,
+    Lines,
+    Groups,
+    Raw,
+}
+
+impl Template {
+    pub const ALL: [Template; 5] = [
+        Template::Blank,
+        Template::Grid,
+        Template::Lines,
+        Template::Groups,
+        Template::Raw,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Template::Blank => "blank",
+            Template::Grid => "grid",
+            Template::Lines => "lines",
+            Template::Groups => "groups",
+            Template::Raw => "raw",
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Template::Blank => include_str!("../templates/blank.rs.template"),
+            Template::Grid => include_str!("../templates/grid.rs.template"),
+            Template::Lines => include_str!("../templates/lines.rs.template"),
+            Template::Groups => include_str!("../templates/groups.rs.template"),
+            Template::Raw => include_str!("../templates/raw.rs.template"),
+        }
+    }
+
+    fn render(&self, day: u32) -> String {
+        format!("{}\n{}", self.source(), TESTS_TEMPLATE).replace("{{day}}", &day.to_string())
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Template::ALL
+            .into_iter()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| {
+                let names = Template::ALL.map(|t| t.name()).join(", ");
+                anyhow!("unknown template {s}, expected one of: {names}")
+            })
+    }
+}
+
+fn solution_file(day: u32) -> String {
+    format!("{SOLUTIONS_DIR}/day{:02}.rs", day)
+}
+
+pub fn new_day(day: u32, template: Template) -> anyhow::Result<()> {
+    if !(1..=25).contains(&day) {
+        bail!("invalid day {day}, expected a value between 1 and 25");
+    }
+
+    create_file(solution_file(day), &template.render(day))?;
+    create_file(input_file(day), "")?;
+    create_file(example_file(day), "")?;
+    update_registry()?;
+
+    Ok(())
+}
+
+// the days of the solution files, which must be named dayNN.rs with NN between 01 and 25
+fn solution_days(dir: impl AsRef<Path>) -> anyhow::Result<Vec<u32>> {
+    let mut days = vec![];
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let day = name
+            .to_str()
+            .and_then(|n| n.strip_prefix("day")?.strip_suffix(".rs"))
+            .filter(|n| n.len() == 2)
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|d| (1..=25).contains(d));
+        days.extend(day);
+    }
+    days.sort();
+
+    Ok(days)
+}
+
+// the registry is rewritten from the solution files, so that it only depends on them
+fn update_registry() -> anyhow::Result<()> {
+    let path = format!("{SOLUTIONS_DIR}/mod.rs");
+    let registry = registry(&solution_days(SOLUTIONS_DIR)?);
+    if fs::read_to_string(&path).is_ok_and(|s| s == registry) {
+        return Ok(());
+    }
+
+    fs::write(&path, registry).with_context(|| format!("unable to write {path}"))?;
+    println!("updated {path}");
+
+    Ok(())
+}
+
+fn registry(days: &[u32]) -> String {
+    let arms = |call: &str| {
+        days.iter().fold(String::new(), |mut s, d| {
+            let call = call.replace("NN", &format!("{d:02}"));
+            _ = writeln!(
+                s,
+                "        #[cfg(aoc_day = \"{d}\")]\n        {d} => Some({call}),"
+            );
+            s
+        })
+    };
+    let mods = days.iter().fold(String::new(), |mut s, d| {
+        _ = writeln!(s, "#[cfg(aoc_day = \"{d}\")]\nmod day{d:02};");
+        s
+    });
+    let exec_all_days = days.iter().fold(String::new(), |mut s, d| {
+        _ = writeln!(
+            s,
+            "    #[cfg(aoc_day = \"{d}\")]\n    outputs.push(({d}, day{d:02}::Problem.solve({d})));"
+        );
+        s
+    });
+
+    REGISTRY_TEMPLATE
+        .replace("{{mods}}", &mods)
+        .replace("{{exec_day}}", &arms("dayNN::Problem.solve(day)"))
+        .replace("{{exec_all_days}}", &exec_all_days)
+        .replace(
+            "{{gen_day}}",
+            &arms("dayNN::Problem.generate(day, params, &mut rng)"),
+        )
+        .replace("{{render_day}}", &arms("dayNN::Problem.render_input(day)"))
+        .replace(
+            "{{image_day}}",
+            &arms("dayNN::Problem.image_input(day, scale)"),
+        )
+        .replace(
+            "{{animate_day}}",
+            &arms("dayNN::Problem.animate_input(day, output)"),
+        )
+        .replace("{{solved}}", &solved(days))
+}
+
+// the statement telling whether a day has a solution, laid out as rustfmt does: the
+// longest pattern of 25 days, 1..=2 | 4..=5 | ... | 25, still fits on the second line
+fn solved(days: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = vec![];
+    for &d in days {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == d => *end = d,
+            _ => ranges.push((d, d)),
+        }
+    }
+    let pattern = ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}..={end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    if pattern.is_empty() {
+        "let solved = false;".to_string()
+    } else if pattern.len() <= 100 - "    let solved = matches!(day, );".len() {
+        format!("let solved = matches!(day, {pattern});")
+    } else {
+        format!("let solved =\n        matches!(day, {pattern});")
+    }
+}
+
+fn create_file<P: AsRef<Path>>(p: P, contents: &str) -> anyhow::Result<()> {
+    let p = p.as_ref();
+    if p.exists() {
+        println!("skipped {} (already exists)", p.display());
+        return Ok(());
+    }
+
+    if let Some(dir) = p.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(p, contents).with_context(|| format!("unable to write {}", p.display()))?;
+    println!("created {}", p.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_up_to_date() {
+        let days = solution_days(SOLUTIONS_DIR).unwrap();
+        let checked_in = fs::read_to_string(format!("{SOLUTIONS_DIR}/mod.rs")).unwrap();
+        assert!(
+            registry(&days) == checked_in,
+            "{SOLUTIONS_DIR}/mod.rs is stale, run `cargo run -- new <day>` to regenerate it"
+        );
+    }
+
+    #[test]
+    fn solved_patterns() {
+        assert_eq!(solved(&[]), "let solved = false;");
+        assert_eq!(solved(&[4]), "let solved = matches!(day, 4);");
+        assert_eq!(
+            solved(&[1, 2, 3, 5, 7, 8]),
+            "let solved = matches!(day, 1..=3 | 5 | 7..=8);"
+        );
+
+        let pairs: Vec<u32> = (1..=25).filter(|d| d % 3 != 0).collect();
+        let statement = solved(&pairs);
+        assert!(statement.starts_with("let solved =\n        matches!(day, 1..=2 | 4..=5 |"));
+        assert_eq!(statement.lines().last().unwrap().len(), 100);
+    }
+}
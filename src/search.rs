@@ -0,0 +1,428 @@
+use fnv::{FnvHashMap, FnvHashSet};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+// Graph searches over any state type, the graph being described by a successor function.
+// Grid searches use coordinates as states, and a successor function based on neighbours4.
+
+pub struct SearchResult<N, C> {
+    pub dist: FnvHashMap<N, C>,
+    pub pred: FnvHashMap<N, N>,
+    // number of shortest paths from any start to each node, not computed by dfs
+    pub paths: FnvHashMap<N, usize>,
+    // the goal which stopped the search, if any
+    pub goal: Option<N>,
+}
+
+impl<N, C> Default for SearchResult<N, C> {
+    fn default() -> Self {
+        Self {
+            dist: FnvHashMap::default(),
+            pred: FnvHashMap::default(),
+            paths: FnvHashMap::default(),
+            goal: None,
+        }
+    }
+}
+
+#[allow(unused)]
+impl<N, C> SearchResult<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy,
+{
+    pub fn distance(&self, n: &N) -> Option<C> {
+        self.dist.get(n).copied()
+    }
+
+    pub fn path_count(&self, n: &N) -> usize {
+        self.paths.get(n).copied().unwrap_or_default()
+    }
+
+    pub fn reached(&self) -> impl Iterator<Item = &N> {
+        self.dist.keys()
+    }
+
+    // the path from a start to n, both included
+    pub fn path_to(&self, n: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(n) {
+            return None;
+        }
+
+        let mut path = vec![n.clone()];
+        let mut current = n;
+        while let Some(p) = self.pred.get(current) {
+            path.push(p.clone());
+            current = p;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+#[allow(unused)]
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> SearchResult<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut result = SearchResult::default();
+    let mut queue = VecDeque::new();
+
+    for s in starts {
+        if result.dist.insert(s.clone(), 0).is_none() {
+            result.paths.insert(s.clone(), 1);
+            queue.push_back(s);
+        }
+    }
+
+    while let Some(n) = queue.pop_front() {
+        if is_goal(&n) {
+            result.goal = Some(n);
+            break;
+        }
+
+        let d = result.dist[&n] + 1;
+        let count = result.paths[&n];
+        for m in successors(&n) {
+            match result.dist.get(&m) {
+                Some(&old) if old == d => *result.paths.entry(m).or_default() += count,
+                Some(_) => {}
+                None => {
+                    result.dist.insert(m.clone(), d);
+                    result.pred.insert(m.clone(), n.clone());
+                    result.paths.insert(m.clone(), count);
+                    queue.push_back(m);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// the distances are the depths at which the nodes were first discovered,
+// they are not the shortest ones
+#[allow(unused)]
+pub fn dfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> SearchResult<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut result = SearchResult::default();
+    let mut stack = vec![];
+
+    for s in starts {
+        if result.dist.insert(s.clone(), 0).is_none() {
+            stack.push(s);
+        }
+    }
+
+    while let Some(n) = stack.pop() {
+        if is_goal(&n) {
+            result.goal = Some(n);
+            break;
+        }
+
+        let d = result.dist[&n] + 1;
+        for m in successors(&n) {
+            if !result.dist.contains_key(&m) {
+                result.dist.insert(m.clone(), d);
+                result.pred.insert(m.clone(), n.clone());
+                stack.push(m);
+            }
+        }
+    }
+
+    result
+}
+
+#[allow(unused)]
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> SearchResult<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, successors, |_| C::default(), is_goal)
+}
+
+// the heuristic must be consistent: it is 0 on the goals, and it never decreases by more than
+// the cost of an edge, h(n) <= cost(n, m) + h(m); never overestimating the remaining cost is
+// not enough, since closed nodes are not reopened when a shorter path to them is found
+#[allow(unused)]
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> SearchResult<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut result = SearchResult::default();
+    let mut closed = FnvHashSet::default();
+    // the heap only holds indices in nodes, so that N doesn't need to be Ord
+    let mut heap = BinaryHeap::new();
+    let mut nodes = vec![];
+
+    for s in starts {
+        if result.dist.insert(s.clone(), C::default()).is_none() {
+            result.paths.insert(s.clone(), 1);
+            heap.push(Reverse((heuristic(&s), nodes.len())));
+            nodes.push(s);
+        }
+    }
+
+    while let Some(Reverse((_, i))) = heap.pop() {
+        let n = nodes[i].clone();
+        if !closed.insert(n.clone()) {
+            continue;
+        }
+
+        if is_goal(&n) {
+            result.goal = Some(n);
+            break;
+        }
+
+        let d = result.dist[&n];
+        let count = result.paths[&n];
+        for (m, cost) in successors(&n) {
+            let nd = d + cost;
+            match result.dist.get(&m) {
+                Some(&old) if old == nd => *result.paths.entry(m).or_default() += count,
+                Some(&old) if old < nd => {}
+                _ => {
+                    result.dist.insert(m.clone(), nd);
+                    result.pred.insert(m.clone(), n.clone());
+                    result.paths.insert(m.clone(), count);
+                    heap.push(Reverse((nd + heuristic(&m), nodes.len())));
+                    nodes.push(m);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// counts all the paths from start to any goal, the paths stop at the first goal they reach,
+// the graph must be acyclic, edges closing a cycle are ignored
+#[allow(unused)]
+pub fn count_paths<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> usize
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut counts = FnvHashMap::default();
+    let mut visiting = FnvHashSet::default();
+    let mut stack = vec![(start.clone(), false)];
+
+    while let Some((n, expanded)) = stack.pop() {
+        if counts.contains_key(&n) {
+            continue;
+        }
+
+        if is_goal(&n) {
+            counts.insert(n, 1);
+        } else if expanded {
+            let total = successors(&n)
+                .into_iter()
+                .map(|m| counts.get(&m).copied().unwrap_or_default())
+                .sum();
+            visiting.remove(&n);
+            counts.insert(n, total);
+        } else if visiting.insert(n.clone()) {
+            let next = successors(&n)
+                .into_iter()
+                .filter(|m| !counts.contains_key(m) && !visiting.contains(m))
+                .collect::<Vec<_>>();
+            stack.push((n, true));
+            stack.extend(next.into_iter().map(|m| (m, false)));
+        }
+    }
+
+    counts.get(&start).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+..#.
+.##.
+....
+#.#.";
+
+    fn open_neighbours(grid: &Grid<u8>) -> impl FnMut(&(usize, usize)) -> Vec<(usize, usize)> + '_ {
+        |&c| {
+            grid.iter_neighbours4_with_coords(c)
+                .filter(|&(_, &v)| v != b'#')
+                .map(|(n, _)| n)
+                .collect()
+        }
+    }
+
+    fn maze() -> Grid<u8> {
+        MAZE.parse().unwrap()
+    }
+
+    #[test]
+    fn bfs_shortest_paths() {
+        let grid = maze();
+        let result = bfs([(0, 0)], open_neighbours(&grid), |_| false);
+
+        assert_eq!(result.distance(&(3, 0)), Some(7));
+        assert_eq!(result.distance(&(1, 3)), Some(4));
+        assert_eq!(result.distance(&(2, 0)), None);
+        assert_eq!(result.reached().count(), 11);
+        assert_eq!(
+            result.path_to(&(3, 0)),
+            Some(vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (3, 1),
+                (3, 0)
+            ])
+        );
+        assert_eq!(result.path_count(&(3, 3)), 1);
+    }
+
+    #[test]
+    fn bfs_counts_shortest_paths() {
+        let grid = Grid::new_with(3, 3, b'.');
+        let result = bfs([(0, 0)], open_neighbours(&grid), |_| false);
+
+        assert_eq!(result.distance(&(2, 2)), Some(4));
+        assert_eq!(result.path_count(&(2, 2)), 6);
+        assert_eq!(result.path_count(&(1, 1)), 2);
+    }
+
+    #[test]
+    fn bfs_stops_at_goal() {
+        let grid = maze();
+        let result = bfs([(0, 0)], open_neighbours(&grid), |&c| c == (1, 2));
+
+        assert_eq!(result.goal, Some((1, 2)));
+        assert_eq!(result.goal_path().map(|p| p.len()), Some(4));
+        assert_eq!(result.distance(&(3, 0)), None);
+    }
+
+    #[test]
+    fn bfs_multiple_starts() {
+        let grid = maze();
+        let result = bfs([(0, 0), (3, 0)], open_neighbours(&grid), |_| false);
+
+        assert_eq!(result.distance(&(3, 2)), Some(2));
+        assert_eq!(result.path_to(&(3, 2)).unwrap()[0], (3, 0));
+    }
+
+    #[test]
+    fn dfs_reaches_the_same_nodes() {
+        let grid = maze();
+        let result = dfs([(0, 0)], open_neighbours(&grid), |_| false);
+        let mut reached = result.reached().copied().collect::<Vec<_>>();
+        reached.sort();
+
+        let mut expected = bfs([(0, 0)], open_neighbours(&grid), |_| false)
+            .reached()
+            .copied()
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(reached, expected);
+
+        let path = result.path_to(&(3, 0)).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert!(path
+            .windows(2)
+            .all(|w| open_neighbours(&grid)(&w[0]).contains(&w[1])));
+    }
+
+    fn weighted(n: &char) -> Vec<(char, u32)> {
+        match n {
+            'a' => vec![('b', 1), ('c', 4)],
+            'b' => vec![('c', 2), ('d', 5)],
+            'c' => vec![('d', 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn dijkstra_weighted_graph() {
+        let result = dijkstra(['a'], weighted, |_| false);
+
+        assert_eq!(result.distance(&'c'), Some(3));
+        assert_eq!(result.distance(&'d'), Some(4));
+        assert_eq!(result.path_to(&'d'), Some(vec!['a', 'b', 'c', 'd']));
+        assert_eq!(result.path_count(&'d'), 1);
+
+        let result = dijkstra(['a'], weighted, |&n| n == 'c');
+        assert_eq!(result.goal, Some('c'));
+        assert_eq!(result.distance(&'c'), Some(3));
+    }
+
+    #[test]
+    fn astar_matches_bfs() {
+        let grid = maze();
+        let goal: (usize, usize) = (3, 3);
+        let manhattan = |&(x, y): &(usize, usize)| goal.0.abs_diff(x) + goal.1.abs_diff(y);
+        let successors = |c: &(usize, usize)| {
+            open_neighbours(&grid)(c)
+                .into_iter()
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let result = astar([(0, 0)], successors, manhattan, |&c| c == goal);
+
+        assert_eq!(result.goal, Some(goal));
+        assert_eq!(result.distance(&goal), Some(6));
+        assert_eq!(result.goal_path().map(|p| p.len()), Some(7));
+    }
+
+    #[test]
+    fn count_paths_in_dag() {
+        let grid = Grid::new_with(3, 3, b'.');
+        let right_or_down = |&(x, y): &(usize, usize)| {
+            [(x + 1, y), (x, y + 1)]
+                .into_iter()
+                .filter(|c| grid.contains_coord(c))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(count_paths((0, 0), right_or_down, |&c| c == (2, 2)), 6);
+        // the paths stop at the first goal
+        assert_eq!(count_paths((0, 0), right_or_down, |&(x, _)| x == 2), 6);
+        assert_eq!(count_paths((0, 0), right_or_down, |_| false), 0);
+    }
+}
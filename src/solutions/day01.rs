@@ -1,3 +1,4 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::BufReadExt;
 use crate::solver::Solver;
 use itertools::Itertools;
@@ -53,4 +54,17 @@ impl Solver for Problem {
             .map(|n| occurrences.get(&n).copied().unwrap_or_default() as u32 * n)
             .sum()
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the number of lines
+        Some(
+            (0..params.size)
+                .map(|_| {
+                    let left = rng.range(10000..100000);
+                    let right = rng.range(10000..100000);
+                    format!("{left}   {right}\n")
+                })
+                .collect(),
+        )
+    }
 }
@@ -1,3 +1,4 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::WhitespaceSeparatedList;
 use crate::solver::Solver;
 use itertools::Itertools;
@@ -30,6 +31,29 @@ impl Solver for Problem {
             .filter(|v| v.iter().any(|d| is_safe(d)))
             .count()
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the number of reports, density is the probability of an unsafe step
+        Some(
+            (0..params.size)
+                .map(|_| {
+                    let direction = if rng.chance(0.5) { 1 } else { -1 };
+                    let mut level = rng.range(10..90) as i32;
+                    let mut levels = vec![level];
+                    for _ in 1..rng.range(5..9) {
+                        let step = if rng.chance(params.density) {
+                            rng.range(0..8) as i32 - 4
+                        } else {
+                            direction * rng.range(1..4) as i32
+                        };
+                        level = (level + step).max(1);
+                        levels.push(level);
+                    }
+                    levels.iter().join(" ") + "\n"
+                })
+                .collect(),
+        )
+    }
 }
 
 fn generate_combinations(input: &[i32]) -> Vec<Vec<i32>> {
@@ -1,3 +1,4 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::ReadAll;
 use crate::solver::Solver;
 use regex::RegexBuilder;
@@ -21,6 +22,25 @@ impl Solver for Problem {
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
         mul_total(&filter_string(input))
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the length of the memory, density is the proportion of valid instructions
+        let noise = [
+            "mul(", "mul[", ")", ",", "do(", "do()", "don't()", "select()", " ", "%", "&",
+        ];
+        let mut s = String::with_capacity(params.size + 16);
+        while s.len() < params.size {
+            if rng.chance(params.density) {
+                s += &format!("mul({},{})", rng.range(1..1000), rng.range(1..1000));
+            } else {
+                let fragment = rng.choose(&noise);
+                s.push_str(fragment);
+            }
+        }
+        s.push('\n');
+
+        Some(s)
+    }
 }
 
 fn mul_total(s: &str) -> u64 {
@@ -1,4 +1,5 @@
-use crate::grid::{Coord, Grid};
+use crate::generator::{GenParams, Rng};
+use crate::grid::{Direction8, Grid};
 use crate::solver::Solver;
 use std::io::BufRead;
 
@@ -14,15 +15,24 @@ impl Solver for Problem {
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        input
-            .iter_with_coords()
-            .filter(|(_, &v)| v == b'X')
-            .map(|(coords, _)| {
+        // look for the word in both directions along every row, column and diagonal
+        let view = input.view();
+        view.rows()
+            .chain(view.transpose().rows())
+            .map(|line| line.copied().collect::<Vec<_>>())
+            .chain(
+                input
+                    .diagonals()
+                    .map(|line| line.map(|(_, &v)| v).collect()),
+            )
+            .chain(
                 input
-                    .neighbours_coords8(coords)
-                    .iter()
-                    .map(|c| c.diff(&coords))
-                    .filter(|&dir| find_sequence_from_point(input, coords, dir))
+                    .anti_diagonals()
+                    .map(|line| line.map(|(_, &v)| v).collect()),
+            )
+            .map(|line| {
+                line.windows(4)
+                    .filter(|&w| w == b"XMAS" || w == b"SAMX")
                     .count()
             })
             .sum()
@@ -34,29 +44,32 @@ impl Solver for Problem {
             .filter(|(c, &v)| v == b'A' && is_xmas_center(input, c))
             .count()
     }
-}
 
-fn find_sequence_from_point(grid: &Grid<u8>, coords: (usize, usize), dir: (isize, isize)) -> bool {
-    let m_pos = coords.add_offset(dir);
-    let a_pos = m_pos.add_offset(dir);
-    let s_pos = a_pos.add_offset(dir);
-    grid.get(m_pos) == Some(&b'M')
-        && grid.get(a_pos) == Some(&b'A')
-        && grid.get(s_pos) == Some(&b'S')
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the width and height of the grid
+        Some(
+            (0..params.size)
+                .map(|_| {
+                    let mut row = (0..params.size)
+                        .map(|_| char::from(*rng.choose(b"XMAS")))
+                        .collect::<String>();
+                    row.push('\n');
+                    row
+                })
+                .collect(),
+        )
+    }
 }
 
 fn is_xmas_center(grid: &Grid<u8>, coords: &(usize, usize)) -> bool {
-    [(-1, -1), (1, -1)]
+    [Direction8::NorthWest, Direction8::NorthEast]
         .iter()
-        .map(|&(x, y)| {
-            let p1 = grid
-                .get(coords.add_offset((x, y)))
-                .copied()
-                .unwrap_or_default();
+        .map(|&dir| {
+            let p1 = grid.ray(coords, dir).next().map_or(0, |(_, &v)| v);
             let p2 = grid
-                .get(coords.add_offset((-x, -y)))
-                .copied()
-                .unwrap_or_default();
+                .ray(coords, dir.turn_around())
+                .next()
+                .map_or(0, |(_, &v)| v);
             (p1, p2)
         })
         .all(|(p1, p2)| p1 == b'M' && p2 == b'S' || p1 == b'S' && p2 == b'M')
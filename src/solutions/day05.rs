@@ -1,3 +1,4 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::{BufReadExt, ReadAll};
 use crate::solver::Solver;
 use fnv::{FnvHashMap, FnvHashSet};
@@ -51,7 +52,7 @@ impl Solver for Problem {
             .filter(|v| {
                 v.iter()
                     .tuple_windows()
-                    .all(|(a, b)| input.rules.get(a).map_or(false, |v| v.contains(b)))
+                    .all(|(a, b)| input.rules.get(a).is_some_and(|v| v.contains(b)))
             })
             .flat_map(|v| find_middle(v))
             .map(u64::from)
@@ -65,13 +66,40 @@ impl Solver for Problem {
             .filter(|v| {
                 v.iter()
                     .tuple_windows()
-                    .any(|(a, b)| !input.rules.get(a).map_or(false, |v| v.contains(b)))
+                    .any(|(a, b)| !input.rules.get(a).is_some_and(|v| v.contains(b)))
             })
             .map(|v| to_correct(v, &input.rules))
             .flat_map(|v| find_middle(&v))
             .map(u64::from)
             .sum()
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the number of updates, density is the proportion of correct updates
+        let mut pages = (10..100).collect::<Vec<u8>>();
+        rng.shuffle(&mut pages);
+
+        let mut s = String::new();
+        for (i, a) in pages.iter().enumerate() {
+            for b in &pages[i + 1..] {
+                s += &format!("{a}|{b}\n");
+            }
+        }
+        s.push('\n');
+
+        for _ in 0..params.size {
+            let mut update = pages.clone();
+            rng.shuffle(&mut update);
+            update.truncate(2 * rng.index(10) + 5);
+            if rng.chance(params.density) {
+                update.sort_by_key(|p| pages.iter().position(|e| e == p));
+            }
+            s += &update.iter().join(",");
+            s.push('\n');
+        }
+
+        Some(s)
+    }
 }
 
 fn find_middle(v: &[u8]) -> Option<u8> {
@@ -87,8 +115,8 @@ fn to_correct(v: &[u8], rules: &FnvHashMap<u8, FnvHashSet<u8>>) -> Vec<u8> {
     let mut output = v.to_vec();
 
     output.sort_by(|a, b| {
-        rules.get(&a).map_or(Ordering::Greater, |v| {
-            if v.contains(&b) {
+        rules.get(a).map_or(Ordering::Greater, |v| {
+            if v.contains(b) {
                 Ordering::Less
             } else {
                 Ordering::Greater
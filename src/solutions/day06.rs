@@ -1,39 +1,45 @@
-use crate::grid::{Coord, Grid};
+use crate::animation::{AnimationOutput, Recorder};
+use crate::generator::{GenParams, Rng};
+use crate::grid::{BitGrid, Coord, Direction, DirectionalBitGrid, Grid};
+use crate::image::{Image, Rgb};
+use crate::padded_grid::PaddedGrid;
+use crate::render::{Color, Renderer};
 use crate::solver::Solver;
 use anyhow::anyhow;
-use fnv::FnvHashSet;
 use rayon::prelude::*;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::io::BufRead;
+use std::iter::{once, successors};
 
 pub struct Problem;
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub struct Vector {
     pos: (usize, usize),
-    dir: (isize, isize),
+    dir: Direction,
 }
 
 impl Vector {
     pub fn new(pos: (usize, usize)) -> Self {
-        Self { pos, dir: (0, -1) }
+        Self {
+            pos,
+            dir: Direction::North,
+        }
     }
 
-    pub fn next(&self) -> (usize, usize) {
-        self.pos.add_offset(self.dir)
+    pub fn next(&self) -> Option<(usize, usize)> {
+        self.pos.checked_add_offset(self.dir)
     }
 
-    pub fn move_forward(&mut self) {
-        self.pos = self.next();
+    pub fn facing_wall(&self, grid: &Grid<Entry>) -> bool {
+        self.next()
+            .and_then(|c| grid.get(c))
+            .is_some_and(|e| e == &Entry::Wall)
     }
 
     pub fn turn_right(&mut self) {
-        self.dir = match self.dir {
-            (0, -1) => (1, 0),
-            (1, 0) => (0, 1),
-            (0, 1) => (-1, 0),
-            (-1, 0) => (0, -1),
-            _ => unreachable!(),
-        };
+        self.dir = self.dir.turn_right();
     }
 }
 
@@ -42,11 +48,29 @@ pub struct Lab {
     start: (usize, usize),
 }
 
+// the guard never leaves the lab, it turned twice in the same way in front of a wall
+#[derive(Debug)]
+pub struct GuardLoop(Vector);
+
+impl Display for GuardLoop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the guard walks in circles, turning again at {:?} facing {}",
+            self.0.pos, self.0.dir
+        )
+    }
+}
+
+impl Error for GuardLoop {}
+
 #[derive(Clone, PartialEq)]
 pub enum Entry {
     Empty,
     Wall,
     Guard,
+    // the border of the padded lab, never parsed
+    Outside,
 }
 
 impl TryFrom<u8> for Entry {
@@ -74,81 +98,283 @@ impl Solver for Problem {
             .find_map(|(c, v)| (*v == Entry::Guard).then_some(c))
             .unwrap();
 
-        Ok(Lab { grid, start })
+        let lab = Lab { grid, start };
+        guard_path(&lab, None)?;
+
+        Ok(lab)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        find_all_visited(input).len()
+        find_all_visited(input, None).count()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut initial_visited = find_all_visited(input);
-
-        // ignore the starting position
-        initial_visited.remove(&input.start);
-
-        // for each point initially visited, we try inserting a wall and try to detect a loop
-        // aka whether we find a vector that we already visited
-        initial_visited
-            .par_iter()
-            .filter(|p| {
-                let mut lab = Lab {
-                    grid: input.grid.clone(),
-                    start: input.start,
-                };
-                if let Some(e) = lab.grid.get_mut(*p) {
-                    *e = Entry::Wall
-                }
+        find_obstructions(input).len()
+    }
 
-                are_we_looping(&lab)
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the width and height of the lab, density is the proportion of walls
+        let mut cells = (0..params.size * params.size)
+            .map(|_| {
+                if rng.chance(params.density) {
+                    b'#'
+                } else {
+                    b'.'
+                }
             })
-            .count()
+            .collect::<Vec<_>>();
+        let guard = rng.index(cells.len());
+        cells[guard] = b'^';
+
+        // the walls the guard keeps turning in front of are removed until it leaves the lab
+        let mut lab = Lab {
+            grid: Grid::from_cells(
+                cells.iter().flat_map(|&b| Entry::try_from(b)).collect(),
+                params.size,
+                params.size,
+            )?,
+            start: (guard % params.size, guard / params.size),
+        };
+        while let Err(GuardLoop(vec)) = guard_path(&lab, None) {
+            let (x, y) = vec.next()?;
+            *lab.grid.get_mut((x, y))? = Entry::Empty;
+            cells[x + y * params.size] = b'.';
+        }
+
+        Some(
+            cells
+                .chunks(params.size)
+                .map(|row| String::from_utf8_lossy(row) + "\n")
+                .collect(),
+        )
     }
-}
 
-fn find_all_visited(input: &Lab) -> FnvHashSet<(usize, usize)> {
-    let mut visited = FnvHashSet::default();
-    let mut vec = Vector::new(input.start);
+    fn render(&self, input: &Self::Input) -> Option<String> {
+        let rendered = Renderer::new(&input.grid, glyph)
+            .path(
+                "guard path",
+                guard_path(input, None).expect("the guard leaves the lab, checked when parsing"),
+                Color::Yellow,
+            )
+            .mark("obstruction", find_obstructions(input), 'O', Color::Red)
+            .render();
+        Some(rendered)
+    }
+
+    fn image(&self, input: &Self::Input, scale: usize) -> Option<Image> {
+        let path = find_all_visited(input, None);
+        let mut obstructions = BitGrid::new(input.grid.w, input.grid.h);
+        for c in find_obstructions(input) {
+            obstructions.insert(c);
+        }
+        Some(Image::from_grid(&input.grid, scale, |c, e| {
+            if obstructions.contains(c) {
+                Color::Red.into()
+            } else if path.contains(c) {
+                Color::Yellow.into()
+            } else if e == &Entry::Wall {
+                Rgb::gray(40)
+            } else {
+                Rgb::WHITE
+            }
+        }))
+    }
 
-    while input.grid.contains_coord(&vec.pos) {
-        visited.insert(vec.pos);
+    fn animate(&self, input: &Self::Input, output: &AnimationOutput) -> Option<anyhow::Result<()>> {
+        let glyphs = input.grid.map(glyph);
+        let mut recorder = Recorder::new(glyphs.clone());
+        find_all_visited(input, Some(&mut recorder));
 
-        while input
+        // then the walk with the first obstruction found, to check the loop detection
+        if let Some(obstruction) = find_obstructions(input).into_iter().min() {
+            let (lab, start) = padded(input);
+            let mut frame = glyphs;
+            if let Some(c) = frame.get_mut(obstruction) {
+                *c = 'O'
+            }
+            recorder.snapshot(&frame);
+            if let Some(obstruction) = lab.index(obstruction) {
+                are_we_looping(&lab, start, obstruction, Some(&mut recorder));
+            }
+        }
+
+        Some(recorder.export(
+            output,
+            |frame| {
+                Renderer::new(frame, |&c| c)
+                    .colors(|_, &c| glyph_color(c))
+                    .render()
+            },
+            |&c| match c {
+                '#' => Rgb::gray(40),
+                c => glyph_color(c).map_or(Rgb::WHITE, Rgb::from),
+            },
+        ))
+    }
+}
+
+fn guard_path(
+    input: &Lab,
+    mut recorder: Option<&mut Recorder<char>>,
+) -> Result<Vec<(usize, usize)>, GuardLoop> {
+    // as when looking for obstructions, a looping guard always comes back to one of its turns
+    let mut turns = DirectionalBitGrid::new(input.grid.w, input.grid.h);
+    let mut path = vec![input.start];
+    let mut vec = Vector::new(input.start);
+    loop {
+        let from = path.len() - 1;
+        for (c, _) in input
             .grid
-            .get(vec.next())
-            .is_some_and(|e| e == &Entry::Wall)
+            .ray_until(vec.pos, vec.dir, |e| e == &Entry::Wall)
         {
+            path.push(c);
+            vec.pos = c;
+        }
+        let blocked = vec.facing_wall(&input.grid);
+        if blocked {
+            if !turns.insert(vec.pos, vec.dir) {
+                return Err(GuardLoop(vec));
+            }
             vec.turn_right();
         }
-
-        vec.move_forward();
+        record_walk(
+            recorder.as_deref_mut(),
+            path[from..].iter().copied(),
+            vec.pos,
+            vec.dir,
+        );
+        if !blocked {
+            return Ok(path);
+        }
     }
+}
 
+fn find_all_visited(input: &Lab, recorder: Option<&mut Recorder<char>>) -> BitGrid {
+    let mut visited = BitGrid::new(input.grid.w, input.grid.h);
+    let path = guard_path(input, recorder).expect("the guard leaves the lab, checked when parsing");
+    for c in path {
+        visited.insert(c);
+    }
     visited
 }
 
-fn are_we_looping(input: &Lab) -> bool {
-    let mut visited = FnvHashSet::default();
-    let mut vec = Vector::new(input.start);
+// the lab surrounded by Outside cells, and the index of the guard in it
+fn padded(input: &Lab) -> (PaddedGrid<Entry>, usize) {
+    let lab = PaddedGrid::from_grid(&input.grid, 1, Entry::Outside);
+    let start = lab.index(input.start).expect("the guard starts in the lab");
+    (lab, start)
+}
+
+fn find_obstructions(input: &Lab) -> Vec<(usize, usize)> {
+    let mut initial_visited = find_all_visited(input, None);
+    // ignore the starting position
+    initial_visited.remove(input.start);
 
-    while input.grid.contains_coord(&vec.pos) {
-        if visited.contains(&vec) {
+    // for each point initially visited, we try inserting a wall and try to detect a loop
+    // aka whether we find a vector that we already visited
+    let (lab, start) = padded(input);
+    initial_visited
+        .iter()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .filter(|&p| {
+            lab.index(p)
+                .is_some_and(|obstruction| are_we_looping(&lab, start, obstruction, None))
+        })
+        .collect()
+}
+
+fn are_we_looping(
+    lab: &PaddedGrid<Entry>,
+    start: usize,
+    obstruction: usize,
+    mut recorder: Option<&mut Recorder<char>>,
+) -> bool {
+    // only the turns are recorded, a looping guard always comes back to one of them
+    let mut visited = DirectionalBitGrid::new(lab.w, lab.h);
+    let offsets = lab.offsets4();
+    let (mut idx, mut dir) = (start, Direction::North);
+    let mut from = idx;
+    loop {
+        let next = lab.neighbour(idx, offsets[dir.index()]);
+        if next != obstruction && lab[next] != Entry::Wall {
+            if lab[next] == Entry::Outside {
+                record_padded_walk(recorder.as_deref_mut(), lab, from, idx, dir, dir);
+                return false;
+            }
+            idx = next;
+            continue;
+        }
+
+        let pos = lab.coords(idx).expect("the guard is in the lab");
+        let looping = !visited.insert(pos, dir);
+        let facing = if looping { dir } else { dir.turn_right() };
+        record_padded_walk(recorder.as_deref_mut(), lab, from, idx, dir, facing);
+        if looping {
             return true;
         }
+        (from, dir) = (idx, facing);
+    }
+}
 
-        visited.insert(vec.clone());
+fn glyph(e: &Entry) -> char {
+    match e {
+        Entry::Empty => '.',
+        Entry::Wall => '#',
+        Entry::Guard => '^',
+        Entry::Outside => ' ',
+    }
+}
 
-        while input
-            .grid
-            .get(vec.next())
-            .is_some_and(|e| e == &Entry::Wall)
-        {
-            vec.turn_right();
-            visited.insert(vec.clone());
-        }
+fn glyph_color(c: char) -> Option<Color> {
+    match c {
+        'X' => Some(Color::Yellow),
+        'O' => Some(Color::Blue),
+        '^' | '>' | 'v' | '<' => Some(Color::Red),
+        _ => None,
+    }
+}
 
-        vec.move_forward();
+// one frame per straight walk, with the cells walked on and the guard at the end
+fn record_walk(
+    recorder: Option<&mut Recorder<char>>,
+    walked: impl Iterator<Item = (usize, usize)>,
+    guard: (usize, usize),
+    facing: Direction,
+) {
+    if let Some(recorder) = recorder {
+        recorder.push(
+            walked
+                .map(|c| (c, 'X'))
+                .chain(once((guard, facing.arrow()))),
+        );
     }
+}
 
-    false
+fn record_padded_walk(
+    recorder: Option<&mut Recorder<char>>,
+    lab: &PaddedGrid<Entry>,
+    from: usize,
+    to: usize,
+    dir: Direction,
+    facing: Direction,
+) {
+    if let (Some(recorder), Some(guard)) = (recorder, lab.coords(to)) {
+        let offset = lab.offset(dir);
+        let walked = successors(Some(from), |&i| (i != to).then(|| lab.neighbour(i, offset)))
+            .filter_map(|i| lab.coords(i));
+        record_walk(Some(recorder), walked, guard, facing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looping_guard() {
+        let input = ".#..\n...#\n#^..\n..#.\n";
+        let e = Problem.parse_input(input.as_bytes()).err().unwrap();
+        assert!(e.is::<GuardLoop>());
+    }
 }
@@ -1,5 +1,7 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::BufReadExt;
 use crate::solver::Solver;
+use itertools::Itertools;
 use rayon::prelude::*;
 use sscanf::sscanf;
 use std::fmt::{Display, Formatter};
@@ -9,6 +11,9 @@ use std::str::FromStr;
 
 pub struct Problem;
 
+// the number of equations of the generated inputs, like the puzzle inputs
+const GENERATED_EQUATIONS: usize = 850;
+
 pub struct Equation {
     total: u64,
     terms: Vec<u64>,
@@ -106,6 +111,38 @@ impl Solver for Problem {
             .map(|eq| eq.total)
             .sum()
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the number of terms of each equation, the total is computed with random
+        // operators, and density is the probability of adding 1 to it (saturating): such an
+        // equation is usually, but not always, impossible to make true. an operator which would
+        // overflow is replaced by an addition, and the term is skipped if that overflows too
+        let ops = [Op::Add, Op::Mul, Op::Concat];
+        Some(
+            (0..GENERATED_EQUATIONS)
+                .map(|_| {
+                    let terms = (0..params.size)
+                        .map(|_| rng.range(1..100))
+                        .collect::<Vec<_>>();
+                    let mut total = terms.iter().skip(1).fold(terms[0], |acc, &t| {
+                        let result = match rng.choose(&ops) {
+                            Op::Add => acc.checked_add(t),
+                            Op::Mul => acc.checked_mul(t),
+                            Op::Concat => 10u64
+                                .checked_pow(t.ilog10() + 1)
+                                .and_then(|m| acc.checked_mul(m))
+                                .and_then(|v| v.checked_add(t)),
+                        };
+                        result.or(acc.checked_add(t)).unwrap_or(acc)
+                    });
+                    if rng.chance(params.density) {
+                        total = total.saturating_add(1);
+                    }
+                    format!("{total}: {}\n", terms.iter().join(" "))
+                })
+                .collect(),
+        )
+    }
 }
 
 fn generate_op_combinations(n: usize) -> Vec<Vec<Op>> {
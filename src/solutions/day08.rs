@@ -1,24 +1,22 @@
+use crate::generator::{GenParams, Rng};
 use crate::grid::{Coord, Grid};
+use crate::render::{Color, Renderer};
 use crate::solver::Solver;
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
 use std::fmt::Display;
 use std::io::BufRead;
+use std::iter::once;
 
 pub struct Problem;
 
-#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
 pub enum Entry {
+    #[default]
     Empty,
     Antenna(u8),
 }
 
-impl Default for Entry {
-    fn default() -> Self {
-        Self::Empty
-    }
-}
-
 impl TryFrom<u8> for Entry {
     type Error = anyhow::Error;
 
@@ -43,22 +41,6 @@ impl Display for Entry {
     }
 }
 
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
-pub struct Vector {
-    pos: (usize, usize),
-    dir: (isize, isize),
-}
-
-impl Vector {
-    pub fn next(&self) -> (usize, usize) {
-        self.pos.add_offset(self.dir)
-    }
-
-    pub fn move_forward(&mut self) {
-        self.pos = self.next();
-    }
-}
-
 impl Solver for Problem {
     type Input = Grid<Entry>;
     type Output1 = usize;
@@ -82,8 +64,8 @@ impl Solver for Problem {
                 v.iter()
                     .cartesian_product(v.iter())
                     .filter(|(a, b)| a != b)
-                    .map(|(a, b)| a.add_offset(a.diff(b)))
-                    .filter(|c| input.contains_coord(c)),
+                    .filter_map(|(a, b)| input.ray(a, a.diff(b)).next())
+                    .map(|(c, _)| c),
             );
         }
 
@@ -91,20 +73,58 @@ impl Solver for Problem {
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let antennas = create_antennas(input);
+        find_all_antinodes(input).len()
+    }
 
-        let mut antinodes = FnvHashSet::default();
-        for (_, v) in antennas.iter() {
-            antinodes.extend(
-                v.iter()
-                    .cartesian_product(v.iter())
-                    .filter(|(a, b)| a != b)
-                    .flat_map(|(a, b)| create_all_antinodes(input, a, a.diff(b))),
-            );
-        }
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the width and height of the map, density is the proportion of antennas
+        let frequencies = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        Some(
+            (0..params.size)
+                .map(|_| {
+                    let mut row = (0..params.size)
+                        .map(|_| {
+                            if rng.chance(params.density) {
+                                char::from(*rng.choose(frequencies))
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>();
+                    row.push('\n');
+                    row
+                })
+                .collect(),
+        )
+    }
 
-        antinodes.len()
+    fn render(&self, input: &Self::Input) -> Option<String> {
+        let (empty, antennas): (Vec<_>, Vec<_>) = find_all_antinodes(input)
+            .into_iter()
+            .partition(|&c| input.get(c) == Some(&Entry::Empty));
+        let rendered = Renderer::new(input, |e| match e {
+            Entry::Empty => '.',
+            Entry::Antenna(b) => *b as char,
+        })
+        .mark("antinode", empty, '#', Color::Red)
+        .highlight("antenna on an antinode", antennas, Color::Red)
+        .render();
+        Some(rendered)
+    }
+}
+
+fn find_all_antinodes(input: &Grid<Entry>) -> FnvHashSet<(usize, usize)> {
+    let antennas = create_antennas(input);
+    let mut antinodes = FnvHashSet::default();
+    for (_, v) in antennas.iter() {
+        antinodes.extend(
+            v.iter()
+                .cartesian_product(v.iter())
+                .filter(|(a, b)| a != b)
+                .flat_map(|(a, b)| create_all_antinodes(input, a, a.diff(b))),
+        );
     }
+    antinodes
 }
 
 fn create_antennas(input: &Grid<Entry>) -> FnvHashMap<Entry, FnvHashSet<(usize, usize)>> {
@@ -127,16 +147,6 @@ fn create_all_antinodes(
     c: &(usize, usize),
     d: (isize, isize),
 ) -> Vec<(usize, usize)> {
-    let mut antinodes = vec![];
-    let mut vec = Vector { pos: *c, dir: d };
-
     // antenna itself
-    antinodes.push(vec.pos);
-
-    while g.contains_coord(&vec.next()) {
-        vec.move_forward();
-        antinodes.push(vec.pos);
-    }
-
-    antinodes
+    once(*c).chain(g.ray(c, d).map(|(c, _)| c)).collect()
 }
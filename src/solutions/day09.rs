@@ -1,5 +1,6 @@
+use crate::generator::{GenParams, Rng};
 use crate::parsing::ReadAll;
-use crate::solver::Solver;
+use crate::solver::{Normalization, Solver};
 use std::cmp::PartialEq;
 use std::io::BufRead;
 use std::sync::Arc;
@@ -23,6 +24,14 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
+    fn normalization(&self) -> Normalization {
+        // the disk map is a single line, a trailing newline would become an extra entry
+        Normalization {
+            trim_trailing_newline: true,
+            ..Normalization::default()
+        }
+    }
+
     fn parse_input<R: BufRead>(&self, r: R) -> anyhow::Result<Self::Input> {
         Ok(parse_input(&r.read_all()))
     }
@@ -32,9 +41,26 @@ impl Solver for Problem {
         compute_checksum(Arc::new(input_clone))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, _input: &Self::Input) -> Self::Output2 {
         0
     }
+
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the length of the disk map
+        let mut s = (0..params.size)
+            .map(|i| {
+                let size = if i % 2 == 0 {
+                    rng.range(1..10)
+                } else {
+                    rng.range(0..10)
+                };
+                char::from(b'0' + size as u8)
+            })
+            .collect::<String>();
+        s.push('\n');
+
+        Some(s)
+    }
 }
 
 fn parse_input(s: &str) -> Vec<Entry> {
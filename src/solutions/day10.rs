@@ -1,8 +1,11 @@
+use crate::generator::{GenParams, Rng};
 use crate::grid::Grid;
+use crate::image::{Image, Rgb};
+use crate::padded_grid::PaddedGrid;
+use crate::render::{Color, Renderer};
+use crate::search::{bfs, count_paths};
 use crate::solver::Solver;
 use anyhow::anyhow;
-use fnv::FnvHashSet;
-use std::collections::VecDeque;
 use std::io::BufRead;
 
 pub struct Problem;
@@ -13,72 +16,120 @@ impl Solver for Problem {
     type Output2 = usize;
 
     fn parse_input<R: BufRead>(&self, r: R) -> anyhow::Result<Self::Input> {
-        Grid::from_reader_callback(r, |e| e.checked_sub(b'0').ok_or(anyhow!("Invalid value")))
+        Ok(Grid::from_reader_callback(r, |e| {
+            e.checked_sub(b'0').ok_or(anyhow!("Invalid value"))
+        })?)
     }
 
     fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        input
-            .iter_with_coords()
-            .filter(|(_, &v)| v == 0)
-            .map(|(coord, _)| trailhead_score(input, coord))
+        let map = padded(input);
+        map.indices()
+            .filter(|&i| map[i] == 0)
+            .map(|i| trailhead_score(&map, i))
             .sum()
     }
 
     fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        input
-            .iter_with_coords()
-            .filter(|(_, &v)| v == 0)
-            .map(|(coord, _)| trailhead_rating(input, coord))
+        let map = padded(input);
+        map.indices()
+            .filter(|&i| map[i] == 0)
+            .map(|i| trailhead_rating(&map, i))
             .sum()
     }
-}
-
-fn trailhead_score(g: &Grid<u8>, coord: (usize, usize)) -> usize {
-    let mut queue = VecDeque::new();
-    let mut nines = FnvHashSet::default();
 
-    // we assume the first element is always 0
-    queue.push_back(coord);
+    fn generate_input(&self, params: &GenParams, rng: &mut Rng) -> Option<String> {
+        // size is the width and height of the map,
+        // density is the probability for a cell to break the diagonal slopes
+        Some(
+            (0..params.size)
+                .map(|y| {
+                    let mut row = (0..params.size)
+                        .map(|x| {
+                            let noise = if rng.chance(params.density) {
+                                rng.index(10)
+                            } else {
+                                0
+                            };
+                            char::from(b'0' + ((x + y + noise) % 10) as u8)
+                        })
+                        .collect::<String>();
+                    row.push('\n');
+                    row
+                })
+                .collect(),
+        )
+    }
 
-    while let Some(coord) = queue.pop_front() {
-        let value = g.get(coord).unwrap();
-        let neighbours = g.neighbours_coords4(coord);
+    fn render(&self, input: &Self::Input) -> Option<String> {
+        let map = &padded(input);
+        let heights = |h| {
+            input
+                .iter_with_coords()
+                .filter(move |(_, &v)| v == h)
+                .map(|(c, _)| c)
+        };
+        // a cell is on a trail when it can be reached from a trailhead and can reach a summit
+        let indices = |h| map.indices().filter(move |&i| map[i] == h);
+        let from_trailheads = bfs(indices(0), |&i| uphill(map, i), |_| false);
+        let to_summits = bfs(indices(9), |&i| downhill(map, i), |_| false);
+        let on_trail = |c: &(usize, usize)| {
+            map.index(c).is_some_and(|i| {
+                from_trailheads.distance(&i).is_some() && to_summits.distance(&i).is_some()
+            })
+        };
 
-        for n_coord in neighbours {
-            if let Some(&v) = g.get(n_coord) {
-                if *value == 8 && v == 9 {
-                    nines.insert(n_coord);
-                } else if v == value + 1 {
-                    queue.push_back(n_coord);
-                }
-            }
-        }
+        let cells = || input.iter_with_coords().map(|(c, _)| c);
+        let rendered = Renderer::new(input, |&v| char::from(b'0' + v))
+            .mark(
+                "off trail",
+                cells().filter(|c| !on_trail(c)),
+                '.',
+                Color::Gray,
+            )
+            .highlight("trail", cells().filter(on_trail), Color::Yellow)
+            .highlight("trailhead", heights(0).filter(on_trail), Color::Green)
+            .highlight("summit", heights(9).filter(on_trail), Color::Red)
+            .render();
+        Some(rendered)
     }
 
-    nines.len()
+    fn image(&self, input: &Self::Input, scale: usize) -> Option<Image> {
+        // a grayscale heightmap, from black at height 0 to white at height 9
+        Some(Image::from_grid(input, scale, |_, &v| {
+            Rgb::gray((v.min(9) as u32 * 255 / 9) as u8)
+        }))
+    }
 }
 
-fn trailhead_rating(g: &Grid<u8>, coord: (usize, usize)) -> usize {
-    let mut queue = VecDeque::new();
-    let mut nines = 0;
+// the border cells are higher than any height, so that no trail leaves the map
+fn padded(g: &Grid<u8>) -> PaddedGrid<u8> {
+    PaddedGrid::from_grid(g, 1, u8::MAX)
+}
 
-    // we assume the first element is always 0
-    queue.push_back(coord);
+fn uphill(g: &PaddedGrid<u8>, idx: usize) -> impl Iterator<Item = usize> + '_ {
+    let value = g[idx];
+    g.offsets4()
+        .into_iter()
+        .map(move |o| g.neighbour(idx, o))
+        .filter(move |&n| g[n] == value + 1)
+}
 
-    while let Some(coord) = queue.pop_front() {
-        let value = g.get(coord).unwrap();
-        let neighbours = g.neighbours_coords4(coord);
+fn downhill(g: &PaddedGrid<u8>, idx: usize) -> impl Iterator<Item = usize> + '_ {
+    let value = g[idx];
+    g.offsets4()
+        .into_iter()
+        .map(move |o| g.neighbour(idx, o))
+        .filter(move |&n| value > 0 && g[n] == value - 1)
+}
 
-        for n_coord in neighbours {
-            if let Some(&v) = g.get(n_coord) {
-                if *value == 8 && v == 9 {
-                    nines += 1;
-                } else if v == value + 1 {
-                    queue.push_back(n_coord);
-                }
-            }
-        }
-    }
+fn trailhead_score(g: &PaddedGrid<u8>, idx: usize) -> usize {
+    // we assume the first element is always 0
+    bfs([idx], |&i| uphill(g, i), |_| false)
+        .reached()
+        .filter(|&&i| g[i] == 9)
+        .count()
+}
 
-    nines
+fn trailhead_rating(g: &PaddedGrid<u8>, idx: usize) -> usize {
+    count_paths(idx, |&i| uphill(g, i), |&i| g[i] == 9)
 }
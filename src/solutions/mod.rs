@@ -1,45 +1,191 @@
-// DO NOT EDIT THIS FILE
+// generated by `aoc new` from the dayNN.rs files of this directory, do not edit
+use crate::animation::AnimationOutput;
+use crate::generator::{GenParams, Rng};
+use crate::image::Image;
 use crate::solver::{ProblemOutput, Solver};
 
+// each day is only compiled in when AOC_DAYS selects it, see build.rs
+#[cfg(aoc_day = "1")]
 mod day01;
+#[cfg(aoc_day = "2")]
 mod day02;
+#[cfg(aoc_day = "3")]
 mod day03;
+#[cfg(aoc_day = "4")]
 mod day04;
+#[cfg(aoc_day = "5")]
 mod day05;
+#[cfg(aoc_day = "6")]
 mod day06;
+#[cfg(aoc_day = "7")]
 mod day07;
+#[cfg(aoc_day = "8")]
 mod day08;
+#[cfg(aoc_day = "9")]
 mod day09;
+#[cfg(aoc_day = "10")]
 mod day10;
 
-
-pub fn exec_day(day: u32) -> Option<ProblemOutput> {
+pub fn exec_day(day: u32) -> Option<anyhow::Result<ProblemOutput>> {
     match day {
+        #[cfg(aoc_day = "1")]
         1 => Some(day01::Problem.solve(day)),
+        #[cfg(aoc_day = "2")]
         2 => Some(day02::Problem.solve(day)),
+        #[cfg(aoc_day = "3")]
         3 => Some(day03::Problem.solve(day)),
+        #[cfg(aoc_day = "4")]
         4 => Some(day04::Problem.solve(day)),
+        #[cfg(aoc_day = "5")]
         5 => Some(day05::Problem.solve(day)),
+        #[cfg(aoc_day = "6")]
         6 => Some(day06::Problem.solve(day)),
+        #[cfg(aoc_day = "7")]
         7 => Some(day07::Problem.solve(day)),
+        #[cfg(aoc_day = "8")]
         8 => Some(day08::Problem.solve(day)),
+        #[cfg(aoc_day = "9")]
         9 => Some(day09::Problem.solve(day)),
+        #[cfg(aoc_day = "10")]
         10 => Some(day10::Problem.solve(day)),
-        _ => None,
+        _ => not_compiled(day),
+    }
+}
+
+// one push per day, since only the compiled ones are run
+#[allow(clippy::vec_init_then_push)]
+pub fn exec_all_days() -> Vec<(u32, anyhow::Result<ProblemOutput>)> {
+    let mut outputs = vec![];
+    #[cfg(aoc_day = "1")]
+    outputs.push((1, day01::Problem.solve(1)));
+    #[cfg(aoc_day = "2")]
+    outputs.push((2, day02::Problem.solve(2)));
+    #[cfg(aoc_day = "3")]
+    outputs.push((3, day03::Problem.solve(3)));
+    #[cfg(aoc_day = "4")]
+    outputs.push((4, day04::Problem.solve(4)));
+    #[cfg(aoc_day = "5")]
+    outputs.push((5, day05::Problem.solve(5)));
+    #[cfg(aoc_day = "6")]
+    outputs.push((6, day06::Problem.solve(6)));
+    #[cfg(aoc_day = "7")]
+    outputs.push((7, day07::Problem.solve(7)));
+    #[cfg(aoc_day = "8")]
+    outputs.push((8, day08::Problem.solve(8)));
+    #[cfg(aoc_day = "9")]
+    outputs.push((9, day09::Problem.solve(9)));
+    #[cfg(aoc_day = "10")]
+    outputs.push((10, day10::Problem.solve(10)));
+    outputs
+}
+
+pub fn gen_day(day: u32, params: &GenParams, seed: u64) -> Option<anyhow::Result<String>> {
+    let mut rng = Rng::new(seed);
+    match day {
+        #[cfg(aoc_day = "1")]
+        1 => Some(day01::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "2")]
+        2 => Some(day02::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "3")]
+        3 => Some(day03::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "4")]
+        4 => Some(day04::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "5")]
+        5 => Some(day05::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "6")]
+        6 => Some(day06::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "7")]
+        7 => Some(day07::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "8")]
+        8 => Some(day08::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "9")]
+        9 => Some(day09::Problem.generate(day, params, &mut rng)),
+        #[cfg(aoc_day = "10")]
+        10 => Some(day10::Problem.generate(day, params, &mut rng)),
+        _ => not_compiled(day),
+    }
+}
+
+pub fn render_day(day: u32) -> Option<anyhow::Result<String>> {
+    match day {
+        #[cfg(aoc_day = "1")]
+        1 => Some(day01::Problem.render_input(day)),
+        #[cfg(aoc_day = "2")]
+        2 => Some(day02::Problem.render_input(day)),
+        #[cfg(aoc_day = "3")]
+        3 => Some(day03::Problem.render_input(day)),
+        #[cfg(aoc_day = "4")]
+        4 => Some(day04::Problem.render_input(day)),
+        #[cfg(aoc_day = "5")]
+        5 => Some(day05::Problem.render_input(day)),
+        #[cfg(aoc_day = "6")]
+        6 => Some(day06::Problem.render_input(day)),
+        #[cfg(aoc_day = "7")]
+        7 => Some(day07::Problem.render_input(day)),
+        #[cfg(aoc_day = "8")]
+        8 => Some(day08::Problem.render_input(day)),
+        #[cfg(aoc_day = "9")]
+        9 => Some(day09::Problem.render_input(day)),
+        #[cfg(aoc_day = "10")]
+        10 => Some(day10::Problem.render_input(day)),
+        _ => not_compiled(day),
+    }
+}
+
+pub fn image_day(day: u32, scale: usize) -> Option<anyhow::Result<Image>> {
+    match day {
+        #[cfg(aoc_day = "1")]
+        1 => Some(day01::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "2")]
+        2 => Some(day02::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "3")]
+        3 => Some(day03::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "4")]
+        4 => Some(day04::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "5")]
+        5 => Some(day05::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "6")]
+        6 => Some(day06::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "7")]
+        7 => Some(day07::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "8")]
+        8 => Some(day08::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "9")]
+        9 => Some(day09::Problem.image_input(day, scale)),
+        #[cfg(aoc_day = "10")]
+        10 => Some(day10::Problem.image_input(day, scale)),
+        _ => not_compiled(day),
+    }
+}
+
+pub fn animate_day(day: u32, output: &AnimationOutput) -> Option<anyhow::Result<()>> {
+    match day {
+        #[cfg(aoc_day = "1")]
+        1 => Some(day01::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "2")]
+        2 => Some(day02::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "3")]
+        3 => Some(day03::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "4")]
+        4 => Some(day04::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "5")]
+        5 => Some(day05::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "6")]
+        6 => Some(day06::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "7")]
+        7 => Some(day07::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "8")]
+        8 => Some(day08::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "9")]
+        9 => Some(day09::Problem.animate_input(day, output)),
+        #[cfg(aoc_day = "10")]
+        10 => Some(day10::Problem.animate_input(day, output)),
+        _ => not_compiled(day),
     }
 }
 
-pub fn exec_all_days() -> Vec<ProblemOutput> {
-    vec![
-        day01::Problem.solve(1),
-        day02::Problem.solve(2),
-        day03::Problem.solve(3),
-        day04::Problem.solve(4),
-        day05::Problem.solve(5),
-        day06::Problem.solve(6),
-        day07::Problem.solve(7),
-        day08::Problem.solve(8),
-        day09::Problem.solve(9),
-        day10::Problem.solve(10),
-    ]
+// None for the days without a solution
+fn not_compiled<T>(day: u32) -> Option<anyhow::Result<T>> {
+    let solved = matches!(day, 1..=10);
+    solved.then(|| Err(anyhow::anyhow!("not compiled in (excluded by AOC_DAYS)")))
 }
@@ -1,14 +1,97 @@
+use crate::animation::AnimationOutput;
+use crate::generator::{GenParams, Rng};
+use crate::image::Image;
+use crate::vault;
+use anyhow::{anyhow, bail, Context};
 use humantime::format_duration;
 use std::fmt::{Display, Formatter};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-fn input_file(day: u32) -> String {
+pub fn input_file(day: u32) -> String {
     format!("input/{:02}.txt", day)
 }
 
+pub fn example_file(day: u32) -> String {
+    format!("input/examples/{:02}.txt", day)
+}
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Clone, Copy, Debug)]
+pub struct Normalization {
+    pub strip_bom: bool,
+    pub unify_line_endings: bool,
+    pub trim_trailing_newline: bool,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self {
+            strip_bom: true,
+            unify_line_endings: true,
+            trim_trailing_newline: false,
+        }
+    }
+}
+
+impl Normalization {
+    pub fn apply(&self, mut data: Vec<u8>) -> Vec<u8> {
+        if self.strip_bom && data.starts_with(BOM) {
+            data.drain(..BOM.len());
+        }
+
+        if self.unify_line_endings && data.contains(&b'\r') {
+            let mut unified = Vec::with_capacity(data.len());
+            let mut bytes = data.iter().peekable();
+            while let Some(&b) = bytes.next() {
+                if b != b'\r' || bytes.peek() != Some(&&b'\n') {
+                    unified.push(b);
+                }
+            }
+            data = unified;
+        }
+
+        if self.trim_trailing_newline {
+            while data.last() == Some(&b'\n') {
+                data.pop();
+            }
+        }
+
+        data
+    }
+}
+
+// a missing or empty plain input falls back to its encrypted copy from the vault,
+// which is decrypted in memory only
+fn read_input(p: &Path) -> anyhow::Result<Vec<u8>> {
+    read_input_with(p, vault::decrypt_file)
+}
+
+// the encrypted copy is only decrypted when the plain file is missing or empty
+fn read_input_with(
+    p: &Path,
+    decrypt: impl FnOnce(PathBuf) -> anyhow::Result<Vec<u8>>,
+) -> anyhow::Result<Vec<u8>> {
+    let encrypted = vault::encrypted_path(p);
+    match fs::read(p) {
+        Ok(data) if !data.is_empty() || !encrypted.exists() => Ok(data),
+        Err(e) if e.kind() != io::ErrorKind::NotFound || !encrypted.exists() => Err(e.into()),
+        _ => decrypt(encrypted),
+    }
+}
+
+// an empty file is the placeholder created by `aoc new`, not an input
+pub fn input_exists(p: &Path) -> bool {
+    fs::metadata(p).is_ok_and(|m| m.len() > 0) || vault::encrypted_path(p).exists()
+}
+
+fn looks_like_windows_file(data: &[u8]) -> bool {
+    data.starts_with(BOM) || data.windows(2).any(|w| w == b"\r\n")
+}
+
 pub struct Timings {
     pub parse_duration: Duration,
     pub part1_duration: Duration,
@@ -52,18 +135,77 @@ pub trait Solver {
     fn solve_first(&self, input: &Self::Input) -> Self::Output1;
     fn solve_second(&self, input: &Self::Input) -> Self::Output2;
 
+    fn normalization(&self) -> Normalization {
+        Normalization::default()
+    }
+
+    fn generate_input(&self, _params: &GenParams, _rng: &mut Rng) -> Option<String> {
+        None
+    }
+
+    fn render(&self, _input: &Self::Input) -> Option<String> {
+        None
+    }
+
+    fn image(&self, _input: &Self::Input, _scale: usize) -> Option<Image> {
+        None
+    }
+
+    fn animate(
+        &self,
+        _input: &Self::Input,
+        _output: &AnimationOutput,
+    ) -> Option<anyhow::Result<()>> {
+        None
+    }
+
     fn load_input<P: AsRef<Path>>(&self, p: P) -> anyhow::Result<Self::Input> {
-        let f = File::open(p)?;
-        self.parse_input(BufReader::new(f))
+        let p = p.as_ref();
+        let data = read_input(p)?;
+        if looks_like_windows_file(&data) {
+            eprintln!(
+                "warning: {} looks like it was saved by a Windows editor (BOM or CRLF line endings)",
+                p.display()
+            );
+        }
+
+        let data = self.normalization().apply(data);
+        self.parse_input(data.as_slice())
     }
 
-    fn solve(&self, day: u32) -> ProblemOutput {
+    fn load_day(&self, day: u32) -> anyhow::Result<Self::Input> {
         let input_file = input_file(day);
+        if !input_exists(Path::new(&input_file)) {
+            bail!("input missing ({input_file})");
+        }
+
+        self.load_input(&input_file)
+            .with_context(|| format!("unable to load input ({input_file})"))
+    }
+
+    fn render_input(&self, day: u32) -> anyhow::Result<String> {
+        self.render(&self.load_day(day)?)
+            .ok_or_else(|| anyhow!("no renderer for day {day}"))
+    }
+
+    fn image_input(&self, day: u32, scale: usize) -> anyhow::Result<Image> {
+        self.image(&self.load_day(day)?, scale)
+            .ok_or_else(|| anyhow!("no image for day {day}"))
+    }
 
+    fn animate_input(&self, day: u32, output: &AnimationOutput) -> anyhow::Result<()> {
+        self.animate(&self.load_day(day)?, output)
+            .ok_or_else(|| anyhow!("no animation for day {day}"))?
+    }
+
+    fn generate(&self, day: u32, params: &GenParams, rng: &mut Rng) -> anyhow::Result<String> {
+        self.generate_input(params, rng)
+            .ok_or_else(|| anyhow!("no generator for day {day}"))
+    }
+
+    fn solve(&self, day: u32) -> anyhow::Result<ProblemOutput> {
         let start = Instant::now();
-        let input = self
-            .load_input(input_file)
-            .expect("unable to open input file");
+        let input = self.load_day(day)?;
         let parse_duration = start.elapsed();
 
         let start = Instant::now();
@@ -74,7 +216,7 @@ pub trait Solver {
         let s2 = self.solve_second(&input);
         let part2_duration = start.elapsed();
 
-        ProblemOutput {
+        Ok(ProblemOutput {
             part1: s1.to_string(),
             part2: s2.to_string(),
             timings: Timings {
@@ -82,6 +224,48 @@ pub trait Solver {
                 part1_duration,
                 part2_duration,
             },
-        }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_fallback() {
+        let dir = std::env::temp_dir().join(format!("aoc-input-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let decrypt = |p: PathBuf| vault::decrypt(&fs::read(p)?, "key");
+        let plain = dir.join("01.txt");
+
+        // neither the plain file nor the encrypted one
+        assert!(!input_exists(&plain));
+        assert!(read_input_with(&plain, decrypt).is_err());
+
+        fs::write(
+            vault::encrypted_path(&plain),
+            vault::encrypt(b"secret", "key").unwrap(),
+        )
+        .unwrap();
+        assert!(input_exists(&plain));
+        assert_eq!(read_input_with(&plain, decrypt).unwrap(), b"secret");
+
+        // the placeholder created by `aoc new` is not an input
+        fs::write(&plain, "").unwrap();
+        assert!(input_exists(&plain));
+        assert_eq!(read_input_with(&plain, decrypt).unwrap(), b"secret");
+
+        fs::write(&plain, "plain").unwrap();
+        let data = read_input_with(&plain, |_| panic!("decrypted with a plain input"));
+        assert_eq!(data.unwrap(), b"plain");
+
+        // without an encrypted copy, an empty file is read as is
+        fs::remove_file(vault::encrypted_path(&plain)).unwrap();
+        fs::write(&plain, "").unwrap();
+        assert!(!input_exists(&plain));
+        assert_eq!(read_input_with(&plain, decrypt).unwrap(), b"");
+
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,200 @@
+use crate::grid::{Grid, Point};
+use fnv::FnvHashMap;
+
+// A grid which can grow in every direction, addressed by signed coordinates.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: FnvHashMap<Point, T>,
+    bounds: Option<(Point, Point)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: FnvHashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+#[allow(unused)]
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // each cell of the dense grid is stored at its coordinates shifted by origin
+    pub fn from_grid(grid: &Grid<T>, origin: Point) -> Self
+    where
+        T: Clone,
+    {
+        let mut sparse = Self::new();
+        for (c, v) in grid.iter_with_coords() {
+            if let Some(pt) = Point::from_coords(c, origin) {
+                sparse.set(pt, v.clone());
+            }
+        }
+
+        sparse
+    }
+
+    // returns the dense grid covering the bounding box, and the coordinates of its (0, 0) cell,
+    // missing cells are filled with the default value
+    pub fn to_grid(&self) -> (Grid<T>, Point)
+    where
+        T: Clone + Default,
+    {
+        let Some((min, max)) = self.bounds else {
+            return (Grid::new(0, 0), Point::default());
+        };
+
+        let mut grid = Grid::new((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize);
+        for (pt, v) in &self.cells {
+            if let Some(cell) = pt.to_coords(min).and_then(|c| grid.get_mut(c)) {
+                *cell = v.clone();
+            }
+        }
+
+        (grid, min)
+    }
+
+    pub fn get(&self, pt: Point) -> Option<&T> {
+        self.cells.get(&pt)
+    }
+
+    pub fn get_mut(&mut self, pt: Point) -> Option<&mut T> {
+        self.cells.get_mut(&pt)
+    }
+
+    pub fn set(&mut self, pt: Point, v: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point::new(min.x.min(pt.x), min.y.min(pt.y)),
+                Point::new(max.x.max(pt.x), max.y.max(pt.y)),
+            ),
+            None => (pt, pt),
+        });
+        self.cells.insert(pt, v)
+    }
+
+    pub fn remove(&mut self, pt: Point) -> Option<T> {
+        let v = self.cells.remove(&pt)?;
+
+        // the bounding box only needs to be computed again if the point was on its edge
+        if let Some((min, max)) = self.bounds {
+            if pt.x == min.x || pt.x == max.x || pt.y == min.y || pt.y == max.y {
+                self.bounds = self.compute_bounds();
+            }
+        }
+
+        Some(v)
+    }
+
+    fn compute_bounds(&self) -> Option<(Point, Point)> {
+        let min_x = self.cells.keys().map(|pt| pt.x).min()?;
+        let max_x = self.cells.keys().map(|pt| pt.x).max()?;
+        let min_y = self.cells.keys().map(|pt| pt.y).min()?;
+        let max_y = self.cells.keys().map(|pt| pt.y).max()?;
+
+        Some((Point::new(min_x, min_y), Point::new(max_x, max_y)))
+    }
+
+    pub fn contains(&self, pt: Point) -> bool {
+        self.cells.contains_key(&pt)
+    }
+
+    // the smallest and largest coordinates of the cells, or None if the grid is empty
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds
+    }
+
+    pub fn origin(&self) -> Option<Point> {
+        self.bounds.map(|(min, _)| min)
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.x - min.x + 1) as usize)
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.y - min.y + 1) as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&pt, v)| (pt, v))
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (pt, v) in iter {
+            grid.set(pt, v);
+        }
+
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_round_trip() {
+        let grid = "ab\ncd".parse::<Grid<u8>>().unwrap();
+        let sparse = SparseGrid::from_grid(&grid, Point::new(-3, 2));
+        assert_eq!(sparse.get(Point::new(-3, 2)), Some(&b'a'));
+        assert_eq!(sparse.get(Point::new(-2, 3)), Some(&b'd'));
+        assert_eq!(
+            sparse.bounds(),
+            Some((Point::new(-3, 2), Point::new(-2, 3)))
+        );
+
+        let (dense, origin) = sparse.to_grid();
+        assert_eq!(origin, Point::new(-3, 2));
+        assert_eq!(dense.as_slice(), grid.as_slice());
+
+        // the missing cells get the default value
+        let sparse: SparseGrid<u8> = [(Point::new(5, -1), b'x'), (Point::new(7, 0), b'y')]
+            .into_iter()
+            .collect();
+        let (dense, origin) = sparse.to_grid();
+        assert_eq!(origin, Point::new(5, -1));
+        assert_eq!(dense.as_slice(), [b'x', 0, 0, 0, 0, b'y']);
+    }
+
+    #[test]
+    fn remove_shrinks_bounds() {
+        let mut sparse: SparseGrid<u8> = [(0, 0), (2, 1), (-1, 3), (1, 1)]
+            .into_iter()
+            .map(|(x, y)| (Point::new(x, y), 0))
+            .collect();
+        assert_eq!(sparse.bounds(), Some((Point::new(-1, 0), Point::new(2, 3))));
+
+        // an inner point does not change the bounds
+        assert_eq!(sparse.remove(Point::new(1, 1)), Some(0));
+        assert_eq!(sparse.bounds(), Some((Point::new(-1, 0), Point::new(2, 3))));
+
+        sparse.remove(Point::new(-1, 3));
+        assert_eq!(sparse.bounds(), Some((Point::new(0, 0), Point::new(2, 1))));
+        assert_eq!((sparse.width(), sparse.height()), (3, 2));
+
+        assert_eq!(sparse.remove(Point::new(-1, 3)), None);
+        sparse.remove(Point::new(0, 0));
+        sparse.remove(Point::new(2, 1));
+        assert!(sparse.is_empty());
+        assert_eq!(sparse.bounds(), None);
+        assert_eq!((sparse.width(), sparse.height()), (0, 0));
+    }
+}
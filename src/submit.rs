@@ -0,0 +1,453 @@
+use crate::client::Client;
+use crate::config::Config;
+use crate::solutions::exec_day;
+use anyhow::{anyhow, bail, Context};
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Incorrect,
+    Wait(Duration),
+    AlreadySolved,
+    Unknown,
+}
+
+impl Verdict {
+    pub fn parse_response(body: &str) -> Self {
+        let wait = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+
+        if body.contains("That's the right answer") {
+            Verdict::Correct
+        } else if body.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if body.contains("your answer is too low") {
+            Verdict::TooLow
+        } else if body.contains("That's not the right answer") {
+            Verdict::Incorrect
+        } else if let Some(c) = wait.captures(body) {
+            let minutes = c
+                .get(1)
+                .map_or(0, |m| m.as_str().parse().unwrap_or_default());
+            let seconds = c[2].parse::<u64>().unwrap_or_default();
+            Verdict::Wait(Duration::from_secs(minutes * 60 + seconds))
+        } else if body.contains("You don't seem to be solving the right level") {
+            Verdict::AlreadySolved
+        } else {
+            Verdict::Unknown
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        matches!(
+            self,
+            Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect
+        )
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too-high"),
+            Verdict::TooLow => write!(f, "too-low"),
+            Verdict::Incorrect => write!(f, "incorrect"),
+            Verdict::Wait(d) => write!(f, "wait-{}", d.as_secs()),
+            Verdict::AlreadySolved => write!(f, "already-solved"),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl FromStr for Verdict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Verdict::Correct),
+            "too-high" => Ok(Verdict::TooHigh),
+            "too-low" => Ok(Verdict::TooLow),
+            "incorrect" => Ok(Verdict::Incorrect),
+            "already-solved" => Ok(Verdict::AlreadySolved),
+            "unknown" => Ok(Verdict::Unknown),
+            s => s
+                .strip_prefix("wait-")
+                .and_then(|n| n.parse().ok())
+                .map(|n| Verdict::Wait(Duration::from_secs(n)))
+                .ok_or_else(|| anyhow!("invalid verdict {s}")),
+        }
+    }
+}
+
+pub struct Attempt {
+    timestamp: u64,
+    day: u32,
+    part: u8,
+    answer: String,
+    verdict: Verdict,
+}
+
+impl Display for Attempt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.timestamp, self.day, self.part, self.answer, self.verdict
+        )
+    }
+}
+
+impl FromStr for Attempt {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split('\t').collect::<Vec<_>>();
+        let [timestamp, day, part, answer, verdict] = fields.as_slice() else {
+            bail!("invalid history line {s:?}");
+        };
+
+        Ok(Self {
+            timestamp: timestamp.parse()?,
+            day: day.parse()?,
+            part: part.parse()?,
+            answer: answer.to_string(),
+            verdict: verdict.parse()?,
+        })
+    }
+}
+
+// every submission is recorded in .aoc/history, one tab separated attempt per line
+pub struct History {
+    attempts: Vec<Attempt>,
+    file: PathBuf,
+}
+
+impl History {
+    pub fn load(config: &Config) -> anyhow::Result<Self> {
+        let file = config.state_dir.join("history");
+        let attempts = match fs::read_to_string(&file) {
+            Ok(s) => s
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(Attempt::from_str)
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e).context("unable to read the answer history"),
+        };
+
+        Ok(Self { attempts, file })
+    }
+
+    pub fn record(
+        &mut self,
+        day: u32,
+        part: u8,
+        answer: &str,
+        verdict: Verdict,
+    ) -> anyhow::Result<()> {
+        let attempt = Attempt {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            day,
+            part,
+            answer: answer.to_string(),
+            verdict,
+        };
+
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file)?;
+        writeln!(f, "{attempt}")?;
+        self.attempts.push(attempt);
+
+        Ok(())
+    }
+
+    // returns the reason why an answer should not be submitted, if any
+    pub fn check(&self, day: u32, part: u8, answer: &str) -> Option<String> {
+        let attempts = self
+            .attempts
+            .iter()
+            .filter(|a| a.day == day && a.part == part)
+            .collect::<Vec<_>>();
+
+        if let Some(a) = attempts.iter().find(|a| a.verdict == Verdict::Correct) {
+            return Some(if a.answer == answer {
+                format!("{answer} was already accepted")
+            } else {
+                format!("the accepted answer is {}, not {answer}", a.answer)
+            });
+        }
+
+        if let Some(a) = attempts
+            .iter()
+            .find(|a| a.answer == answer && a.verdict.is_wrong())
+        {
+            return Some(format!("{answer} was already rejected ({})", a.verdict));
+        }
+
+        let n = answer.parse::<i128>().ok()?;
+        let bound = |verdict: Verdict| {
+            attempts
+                .iter()
+                .filter(move |a| a.verdict == verdict)
+                .flat_map(|a| a.answer.parse::<i128>())
+        };
+        if let Some(high) = bound(Verdict::TooHigh).filter(|&h| n >= h).min() {
+            return Some(format!("{answer} is too high, {high} already was"));
+        }
+        if let Some(low) = bound(Verdict::TooLow).filter(|&l| n <= l).max() {
+            return Some(format!("{answer} is too low, {low} already was"));
+        }
+
+        None
+    }
+}
+
+pub fn submit(day: u32, part: u8) -> anyhow::Result<()> {
+    if !(1..=2).contains(&part) {
+        bail!("invalid part {part}, expected 1 or 2");
+    }
+
+    let output = exec_day(day).ok_or_else(|| anyhow!("day {day} not found"))??;
+    let answer = if part == 1 {
+        output.part1
+    } else {
+        output.part2
+    };
+
+    match submit_answer(&Config::load()?, day, part, &answer)? {
+        Verdict::Correct => println!("{answer} is the right answer"),
+        Verdict::TooHigh => println!("{answer} is not the right answer, it is too high"),
+        Verdict::TooLow => println!("{answer} is not the right answer, it is too low"),
+        Verdict::Incorrect => println!("{answer} is not the right answer"),
+        Verdict::Wait(d) => println!(
+            "answer submitted too recently, wait {} before trying again",
+            humantime::format_duration(d)
+        ),
+        Verdict::AlreadySolved => println!("day {day} part {part} is already solved"),
+        Verdict::Unknown => println!("unable to understand the response"),
+    }
+
+    Ok(())
+}
+
+// the answer is only posted if the history doesn't already tell whether it is right
+fn submit_answer(config: &Config, day: u32, part: u8, answer: &str) -> anyhow::Result<Verdict> {
+    let mut history = History::load(config)?;
+    if let Some(reason) = history.check(day, part, answer) {
+        bail!("refusing to submit: {reason}");
+    }
+
+    let client = Client::new(config)?;
+    let verdict = Verdict::parse_response(&client.post_answer(day, part, answer)?);
+    history.record(day, part, answer, verdict.clone())?;
+
+    Ok(verdict)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn verdicts() {
+        let parse = Verdict::parse_response;
+
+        assert_eq!(
+            parse("<p>That's the right answer! You are one gold star closer.</p>"),
+            Verdict::Correct
+        );
+        assert_eq!(
+            parse("<p>That's not the right answer; your answer is too high.</p>"),
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            parse("<p>That's not the right answer; your answer is too low.</p>"),
+            Verdict::TooLow
+        );
+        assert_eq!(
+            parse("<p>That's not the right answer. If you're stuck, make sure...</p>"),
+            Verdict::Incorrect
+        );
+        assert_eq!(
+            parse("<p>You gave an answer too recently. You have 34s left to wait.</p>"),
+            Verdict::Wait(Duration::from_secs(34))
+        );
+        assert_eq!(
+            parse("<p>You gave an answer too recently. You have 4m 2s left to wait.</p>"),
+            Verdict::Wait(Duration::from_secs(242))
+        );
+        assert_eq!(
+            parse(
+                "<p>You don't seem to be solving the right level. Did you already complete it?</p>"
+            ),
+            Verdict::AlreadySolved
+        );
+        assert_eq!(parse("<html></html>"), Verdict::Unknown);
+    }
+
+    #[test]
+    fn history_lines() {
+        for verdict in [
+            Verdict::Correct,
+            Verdict::TooHigh,
+            Verdict::TooLow,
+            Verdict::Incorrect,
+            Verdict::Wait(Duration::from_secs(60)),
+            Verdict::AlreadySolved,
+            Verdict::Unknown,
+        ] {
+            assert_eq!(verdict.to_string().parse::<Verdict>().unwrap(), verdict);
+        }
+        assert!("wait-".parse::<Verdict>().is_err());
+
+        let attempt = "1733000000\t6\t2\t1234\ttoo-low"
+            .parse::<Attempt>()
+            .unwrap();
+        assert_eq!((attempt.day, attempt.part), (6, 2));
+        assert_eq!(attempt.verdict, Verdict::TooLow);
+        assert_eq!(attempt.to_string(), "1733000000\t6\t2\t1234\ttoo-low");
+        assert!("1733000000\t6\t2\t1234".parse::<Attempt>().is_err());
+    }
+
+    fn history(attempts: &[(u8, &str, Verdict)]) -> History {
+        History {
+            attempts: attempts
+                .iter()
+                .map(|(part, answer, verdict)| Attempt {
+                    timestamp: 0,
+                    day: 1,
+                    part: *part,
+                    answer: answer.to_string(),
+                    verdict: verdict.clone(),
+                })
+                .collect(),
+            file: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn known_answers() {
+        let h = history(&[
+            (1, "100", Verdict::TooHigh),
+            (1, "10", Verdict::TooLow),
+            (1, "50", Verdict::Incorrect),
+            (1, "42", Verdict::Wait(Duration::from_secs(30))),
+            (2, "7", Verdict::Correct),
+        ]);
+
+        // duplicates of rejected answers
+        assert!(h.check(1, 1, "50").unwrap().contains("already rejected"));
+        assert!(h.check(1, 1, "100").unwrap().contains("already rejected"));
+        // outside of the known bounds
+        assert!(h.check(1, 1, "150").unwrap().contains("too high"));
+        assert!(h.check(1, 1, "5").unwrap().contains("too low"));
+        // between the bounds, or not rejected yet
+        assert_eq!(h.check(1, 1, "60"), None);
+        assert_eq!(h.check(1, 1, "42"), None);
+        assert_eq!(h.check(1, 1, "abc"), None);
+        assert_eq!(h.check(2, 1, "100"), None);
+
+        // nothing else is submitted once the answer is known
+        assert!(h.check(1, 2, "7").unwrap().contains("already accepted"));
+        assert!(h
+            .check(1, 2, "8")
+            .unwrap()
+            .contains("the accepted answer is 7"));
+    }
+
+    // serves the given responses, one connection each, and returns the requests
+    pub(crate) fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut requests = vec![];
+            for body in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                let mut len = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        len = v.trim().parse().unwrap();
+                    }
+                    request += &line;
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut form = vec![0; len];
+                reader.read_exact(&mut form).unwrap();
+                request += &String::from_utf8(form).unwrap();
+                requests.push(request);
+
+                write!(
+                    &stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn submit_to_server() {
+        let (base_url, server) = serve(vec![
+            "<p>That's not the right answer; your answer is too low.</p>",
+            "<p>That's the right answer! You are one gold star closer.</p>",
+        ]);
+        let state_dir = std::env::temp_dir().join(format!("aoc-submit-{}", std::process::id()));
+        let config = Config {
+            session: Some("token".to_string()),
+            base_url,
+            min_delay: Duration::ZERO,
+            state_dir: state_dir.clone(),
+        };
+
+        assert_eq!(submit_answer(&config, 3, 1, "42").unwrap(), Verdict::TooLow);
+        // refused from the history, without a request
+        let e = submit_answer(&config, 3, 1, "40").unwrap_err();
+        assert!(e.to_string().contains("too low"));
+        assert_eq!(
+            submit_answer(&config, 3, 1, "50").unwrap(),
+            Verdict::Correct
+        );
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("POST /2024/day/3/answer HTTP/1.1"));
+        assert!(requests[0].contains("session=token"));
+        assert!(requests[0].ends_with("level=1&answer=42"));
+        assert!(requests[1].ends_with("level=1&answer=50"));
+
+        let history = History::load(&config).unwrap();
+        assert_eq!(history.attempts.len(), 2);
+        assert_eq!(history.attempts[1].verdict, Verdict::Correct);
+        assert!(history.check(3, 1, "50").is_some());
+
+        fs::remove_dir_all(state_dir).unwrap();
+    }
+}
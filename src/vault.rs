@@ -0,0 +1,165 @@
+use crate::solver::input_file;
+use anyhow::{anyhow, bail, Context};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+const KEY_VAR: &str = "AOC_VAULT_KEY";
+const MAGIC: &[u8] = b"AOCVAULT1";
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+// encrypted inputs are stored next to the plain ones, input/NN.txt is stored as input/NN.txt.enc
+pub fn encrypted_path<P: AsRef<Path>>(p: P) -> PathBuf {
+    let mut s = p.as_ref().as_os_str().to_owned();
+    s.push(".enc");
+    PathBuf::from(s)
+}
+
+pub fn is_configured() -> bool {
+    passphrase().is_ok()
+}
+
+fn passphrase() -> anyhow::Result<String> {
+    env::var(KEY_VAR)
+        .ok()
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| anyhow!("no vault key, set {KEY_VAR}"))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> anyhow::Result<LessSafeKey> {
+    let mut key = [0; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| anyhow!("invalid key"))?;
+
+    Ok(LessSafeKey::new(key))
+}
+
+// layout: magic | salt | nonce | ciphertext and tag
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let rng = SystemRandom::new();
+    let mut salt = [0; SALT_LEN];
+    let mut nonce = [0; NONCE_LEN];
+    rng.fill(&mut salt)
+        .map_err(|_| anyhow!("unable to generate a salt"))?;
+    rng.fill(&mut nonce)
+        .map_err(|_| anyhow!("unable to generate a nonce"))?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let mut data = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(MAGIC),
+        &mut data,
+    )
+    .map_err(|_| anyhow!("unable to encrypt"))?;
+
+    Ok([MAGIC, &salt, &nonce, &data].concat())
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let data = data
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| anyhow!("not a vault file"))?;
+    if data.len() < SALT_LEN + NONCE_LEN {
+        bail!("truncated vault file");
+    }
+    let (salt, data) = data.split_at(SALT_LEN);
+    let (nonce, data) = data.split_at(NONCE_LEN);
+
+    let key = derive_key(passphrase, salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+    let mut data = data.to_vec();
+    let len = key
+        .open_in_place(nonce, Aad::from(MAGIC), &mut data)
+        .map_err(|_| anyhow!("unable to decrypt, wrong key or corrupted file"))?
+        .len();
+    data.truncate(len);
+
+    Ok(data)
+}
+
+pub fn decrypt_file<P: AsRef<Path>>(p: P) -> anyhow::Result<Vec<u8>> {
+    let p = p.as_ref();
+    let data = fs::read(p)?;
+    decrypt(&data, &passphrase()?).with_context(|| format!("unable to decrypt {}", p.display()))
+}
+
+// the plaintext is read from the given file, or from stdin if it is "-"
+pub fn add(day: u32, source: Option<&str>) -> anyhow::Result<PathBuf> {
+    let input_file = input_file(day);
+    let source = source.unwrap_or(&input_file);
+
+    let plaintext = if source == "-" {
+        let mut data = vec![];
+        io::stdin().read_to_end(&mut data)?;
+        data
+    } else {
+        fs::read(source).with_context(|| format!("unable to read {source}"))?
+    };
+    store(&input_file, &plaintext)
+}
+
+// the plaintext is stored encrypted next to the plain file p, which is not written
+pub fn store<P: AsRef<Path>>(p: P, plaintext: &[u8]) -> anyhow::Result<PathBuf> {
+    if plaintext.is_empty() {
+        bail!("refusing to store an empty input");
+    }
+
+    let path = encrypted_path(p);
+    fs::write(&path, encrypt(plaintext, &passphrase()?)?)
+        .with_context(|| format!("unable to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+pub fn extract(day: u32) -> anyhow::Result<Vec<u8>> {
+    decrypt_file(encrypted_path(input_file(day)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data = encrypt(b"1 2 3\n", "key").unwrap();
+        assert!(data.starts_with(MAGIC));
+        assert_eq!(decrypt(&data, "key").unwrap(), b"1 2 3\n");
+
+        // a new salt and nonce each time
+        assert_ne!(encrypt(b"1 2 3\n", "key").unwrap(), data);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let data = encrypt(b"1 2 3\n", "key").unwrap();
+
+        let e = decrypt(&data, "other").unwrap_err();
+        assert!(e.to_string().contains("wrong key"));
+
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&tampered, "key").is_err());
+
+        // cut in the header, then in the ciphertext
+        let e = decrypt(&data[..MAGIC.len() + SALT_LEN], "key").unwrap_err();
+        assert_eq!(e.to_string(), "truncated vault file");
+        assert!(decrypt(&data[..data.len() - 1], "key").is_err());
+
+        let e = decrypt(&data[MAGIC.len()..], "key").unwrap_err();
+        assert_eq!(e.to_string(), "not a vault file");
+        assert!(decrypt(b"", "key").is_err());
+    }
+}